@@ -0,0 +1,100 @@
+//! Copies of the ledger/networth files kept before they are replaced.
+
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// A copy of a file, stored as it was (encrypted, if it was).
+#[derive(Debug)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub time: NaiveDateTime,
+    pub size: u64,
+}
+
+/// Copy the file into the directory, named after it and the current time (e.g.
+/// `ledger-20240131-093000.csv`), and remove the oldest copies beyond the number to keep.
+///
+/// When there is already a copy from the same second, that one is kept.
+pub fn store(directory: &str, filepath: &str, keep: usize) -> anyhow::Result<()> {
+    if keep == 0 || !Path::new(filepath).exists() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Could not create the directory of backups '{}'", directory))?;
+
+    let (stem, extension) = parts(filepath);
+
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT);
+    let path = Path::new(directory).join(format!("{}-{}{}", stem, timestamp, extension));
+
+    if !path.exists() {
+        fs::copy(filepath, &path)
+            .with_context(|| format!("Could not back up '{}' to '{}'", filepath, path.display()))?;
+    }
+
+    for backup in list(directory, filepath)?.iter().skip(keep) {
+        fs::remove_file(&backup.path)?;
+    }
+
+    Ok(())
+}
+
+/// Backups of the file in the directory, from the most recent to the oldest.
+pub fn list(directory: &str, filepath: &str) -> anyhow::Result<Vec<Backup>> {
+    let mut result = Vec::new();
+
+    if !Path::new(directory).is_dir() {
+        return Ok(result);
+    }
+
+    let (stem, extension) = parts(filepath);
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        let timestamp = match name
+            .strip_prefix(&format!("{}-", stem))
+            .and_then(|v| v.strip_suffix(&extension))
+        {
+            Some(value) => value.to_string(),
+            None => continue,
+        };
+
+        if let Ok(time) = NaiveDateTime::parse_from_str(&timestamp, TIMESTAMP_FORMAT) {
+            result.push(Backup {
+                path: entry.path(),
+                time,
+                size: entry.metadata()?.len(),
+            });
+        }
+    }
+
+    result.sort_by_key(|backup| std::cmp::Reverse(backup.time));
+
+    Ok(result)
+}
+
+// Name of the file without and with the extension (including the dot).
+fn parts(filepath: &str) -> (String, String) {
+    let path = Path::new(filepath);
+
+    let stem = path
+        .file_stem()
+        .map(|v| v.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let extension = path
+        .extension()
+        .map(|v| format!(".{}", v.to_string_lossy()))
+        .unwrap_or_default();
+
+    (stem, extension)
+}
@@ -0,0 +1,14 @@
+use clap::Parser;
+
+use crate::password;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Seconds during which the password is kept
+    #[arg(short, long, default_value_t = 300)]
+    timeout: u64,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    password::serve(args.timeout)
+}
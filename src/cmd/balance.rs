@@ -1,13 +1,16 @@
 use clap::Parser;
 use prettytable::{format, Cell, Row, Table};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
-use std::ops::AddAssign;
 
 use crate::config::Config;
 use crate::entity::{date::Date, line::Line, line::Liner, money::Money, total::Total};
 use crate::exchange::Exchange;
 use crate::filter::Filter;
+use crate::output::{self, Format, Render, Section};
 use crate::resource::Resource;
 use crate::{util, Mode};
 
@@ -21,29 +24,29 @@ pub struct Args {
     pub date: Option<Date>,
 }
 
-pub fn run(args: Args) -> anyhow::Result<()> {
+pub fn run(args: Args, format: Format) -> anyhow::Result<()> {
     let config = Config::new()?;
 
-    args.calculate(&config)
+    args.calculate(&config, format)
 }
 
 impl Args {
-    fn calculate(&self, config: &Config) -> anyhow::Result<()> {
+    fn calculate(&self, config: &Config, format: Format) -> anyhow::Result<()> {
         let exchange = Exchange::new(config)?;
 
         let filter = Filter::balance(self);
 
         let mut total = Total::new(Some(&config.currency), config, filter.end)?;
 
-        let report = Report::new(&mut total, config, &exchange, &filter)?;
+        let mut report = Report::new(&mut total, config, &exchange, &filter)?;
 
-        let summary = Summary::new(total);
-
-        report.display(self);
+        if !self.all {
+            report.items.retain(|_, item| !item.value.zero());
+        }
 
-        summary.display();
+        let summary = Summary::new(total);
 
-        Ok(())
+        output::print(format, &[("balance", &report), ("summary", &summary)])
     }
 }
 
@@ -83,7 +86,7 @@ impl Report {
                 return Ok(());
             }
 
-            report.add(Item::new(record));
+            report.add(Item::new(record))?;
 
             Ok(())
         })?;
@@ -91,14 +94,18 @@ impl Report {
         Ok(report)
     }
 
-    fn add(&mut self, item: Item) {
-        self.items
-            .entry(item.account.to_string())
-            .and_modify(|i| *i += item.clone())
-            .or_insert(item);
+    fn add(&mut self, item: Item) -> crate::Result<()> {
+        match self.items.entry(item.account.to_string()) {
+            Entry::Occupied(mut existing) => existing.get_mut().add(item)?,
+            Entry::Vacant(entry) => {
+                entry.insert(item);
+            }
+        };
+
+        Ok(())
     }
 
-    fn display(&self, args: &Args) {
+    fn display(&self) {
         let mut table = Table::new();
 
         table.set_format(format::FormatBuilder::new().padding(3, 5).build());
@@ -108,16 +115,14 @@ impl Report {
         table.add_row(Report::headers());
 
         for item in self.items.values() {
-            if args.all || !item.value.zero() {
-                table.add_row(item.row());
-            }
+            table.add_row(item.row());
         }
 
         table.printstd();
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct Item {
     account: String,
     value: Money,
@@ -131,6 +136,12 @@ impl Item {
         }
     }
 
+    fn add(&mut self, other: Item) -> crate::Result<()> {
+        self.value = self.value.checked_add(other.value)?;
+
+        Ok(())
+    }
+
     fn row(&self) -> Row {
         Row::new(vec![
             Cell::new(&self.account).style_spec("brFW"),
@@ -139,15 +150,6 @@ impl Item {
     }
 }
 
-impl AddAssign for Item {
-    fn add_assign(&mut self, other: Self) {
-        *self = Self {
-            account: self.account.to_string(),
-            value: self.value + other.value,
-        }
-    }
-}
-
 #[derive(Debug)]
 struct Summary {
     total: Total,
@@ -168,7 +170,7 @@ impl Summary {
         ])
     }
 
-    pub fn display(self) {
+    pub fn display(&self) {
         let mut table = Table::new();
 
         table.set_format(
@@ -188,3 +190,66 @@ impl Summary {
         table.printstd();
     }
 }
+
+impl Serialize for Report {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let items: Vec<&Item> = self.items.values().collect();
+
+        let mut state = serializer.serialize_struct("Report", 1)?;
+        state.serialize_field("items", &items)?;
+        state.end()
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Balance", &["Account", "Amount"]);
+
+        for item in self.items.values() {
+            section
+                .rows
+                .push(vec![item.account.to_string(), item.value.to_storage()]);
+        }
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self);
+
+        Ok(())
+    }
+}
+
+impl Serialize for Summary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let amount = self.total.amount();
+
+        let mut state = serializer.serialize_struct("Summary", 2)?;
+        state.serialize_field("currency", &amount.currency())?;
+        state.serialize_field("total", &amount)?;
+        state.end()
+    }
+}
+
+impl Render for Summary {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Totals", &["Total"]);
+
+        section.rows.push(vec![self.total.amount().to_storage()]);
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Summary::display(self);
+
+        Ok(())
+    }
+}
@@ -1,10 +1,21 @@
+use anyhow::anyhow;
 use clap::Parser;
+use prettytable::{format, Cell, Row, Table};
 
-use std::io;
-use std::io::prelude::*;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Editor, Helper};
+
+use std::collections::{BTreeSet, HashMap};
 
 use crate::config::Config;
-use crate::entity::line::Line;
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::entity::money::Currency;
 use crate::resource::Resource;
 
 // This usage is necessary because, unfortunately, clap does not handle empty values as expected
@@ -14,9 +25,44 @@ pub static DEFAULT_EMPTY: &str = " ";
 
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Define the list of values that compose an transaction/entry
-    #[arg(short, long, allow_hyphen_values = true)]
+    /// Define the list of values that compose an transaction/entry (all of them, in order, or
+    /// only some as key=value pairs, e.g. category=Food); every argument after it is a value,
+    /// even if it starts with a hyphen
+    #[arg(short, long, num_args = 1.., allow_hyphen_values = true)]
     attributes: Vec<String>,
+    /// Account of the transaction
+    #[arg(long)]
+    account: Option<String>,
+    /// Date of the transaction/entry (defaults to today)
+    #[arg(long)]
+    date: Option<Date>,
+    /// Category of the transaction
+    #[arg(long)]
+    category: Option<String>,
+    /// Description of the transaction
+    #[arg(long)]
+    description: Option<String>,
+    /// Quantity of the transaction (e.g. number of shares)
+    #[arg(long)]
+    quantity: Option<String>,
+    /// Venue of the transaction
+    #[arg(long)]
+    venue: Option<String>,
+    /// Amount of the transaction/entry
+    #[arg(long, allow_hyphen_values = true)]
+    amount: Option<String>,
+    /// Currency of the transaction/entry (defaults to the one usually used by the account)
+    #[arg(long)]
+    currency: Option<String>,
+    /// Trip of the transaction
+    #[arg(long)]
+    trip: Option<String>,
+    /// Amount invested of the entry
+    #[arg(long, allow_hyphen_values = true)]
+    invested: Option<String>,
+    /// Value of the investments of the entry
+    #[arg(long, allow_hyphen_values = true)]
+    investment: Option<String>,
     #[arg(
         value_enum,
         default_value = "ledger",
@@ -39,45 +85,408 @@ impl Args {
     fn book(&self, config: &Config) -> anyhow::Result<()> {
         let mut resource = Resource::new(config, self.mode)?;
 
-        let mut values = self.attributes.clone();
+        let named = self.named()?;
 
-        if values.is_empty() {
-            self.collect_attributes(&mut values, &resource)?
+        let line = if !named.is_empty() {
+            self.build(&named, &mut resource, config)?
+        } else if self.attributes.is_empty() {
+            match self.collect_attributes(&mut resource, config)? {
+                Some(line) => line,
+                None => {
+                    crate::wout!("Nothing was booked");
+                    return Ok(());
+                }
+            }
         } else {
+            let mut values = self.attributes.clone();
+
             for val in values.iter_mut() {
                 if val == DEFAULT_EMPTY {
                     val.clear()
                 }
             }
+
+            Line::build(values, self.mode)?
         };
 
-        let line = Line::build(values, self.mode)?;
+        unlinked_transfer(&line, config)?;
 
         resource.book(&[line])
     }
 
+    /// Values given by name, either with the flags or as key=value pairs (only when all the
+    /// attributes are pairs, otherwise they are the values in order).
+    fn named(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let flags = [
+            ("Account", &self.account),
+            ("Date", &self.date.map(|v| v.to_string())),
+            ("Category", &self.category),
+            ("Description", &self.description),
+            ("Quantity", &self.quantity),
+            ("Venue", &self.venue),
+            ("Amount", &self.amount),
+            ("Currency", &self.currency),
+            ("Trip", &self.trip),
+            ("Invested", &self.invested),
+            ("Investment", &self.investment),
+        ];
+
+        let mut result: Vec<(String, String)> = flags
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| (name.to_string(), v.to_string())))
+            .collect();
+
+        let pairs: Vec<(&str, &str)> = self
+            .attributes
+            .iter()
+            .filter_map(|attribute| attribute.split_once('='))
+            .collect();
+
+        if !self.attributes.is_empty() && pairs.len() == self.attributes.len() {
+            result.extend(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.trim().to_string(), v.to_string())),
+            );
+        } else if !self.attributes.is_empty() && !result.is_empty() {
+            return Err(anyhow!(
+                "Values in order (--attributes) cannot be combined with named values"
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// Build the line from the values given by name, with the date defaulting to today and the
+    /// currency to the one of the last transaction of the account.
+    fn build(
+        &self,
+        named: &[(String, String)],
+        resource: &mut Resource,
+        config: &Config,
+    ) -> anyhow::Result<Line> {
+        let headers: Vec<String> = resource.headers().iter().map(|v| v.to_string()).collect();
+
+        let mut values = vec![String::new(); headers.len()];
+
+        for (key, value) in named.iter() {
+            match headers
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(key))
+            {
+                Some(index) => values[index] = value.to_string(),
+                None => {
+                    return Err(anyhow!(
+                        "Unknown field '{}' (expected one of: {})",
+                        key,
+                        headers.join(", ")
+                    ))
+                }
+            }
+        }
+
+        for (index, name) in headers.iter().enumerate() {
+            if !values[index].is_empty() {
+                continue;
+            }
+
+            values[index] = match name.as_str() {
+                "Date" => Date::today().to_string(),
+                "Currency" => {
+                    let account = headers
+                        .iter()
+                        .position(|name| name == "Account")
+                        .map(|index| values[index].to_string());
+
+                    Suggestions::new(resource, self.mode)?.currency(account.as_ref(), config)
+                }
+                _ => continue,
+            };
+        }
+
+        let investment = headers
+            .iter()
+            .zip(values.iter())
+            .any(|(name, value)| name == "Category" && *value == config.investments);
+
+        for (name, value) in headers.iter().zip(values.iter()) {
+            validate(name, value, investment)?;
+        }
+
+        Line::build(values, self.mode)
+    }
+
+    /// Prompt each of the values, validating them as they are entered, and confirm the line
+    /// before it is booked (`None` if it was not confirmed).
     fn collect_attributes(
         &self,
-        values: &mut Vec<String>,
-        resource: &Resource,
-    ) -> anyhow::Result<()> {
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-
-        for name in resource.headers().iter() {
-            handle
-                .write_all(format!("{}: ", name).as_bytes())
-                .and_then(|_v| handle.flush())?;
-
-            let value = io::stdin()
-                .lock()
-                .lines()
-                .next()
-                .unwrap_or_else(|| Ok("".to_string()))?;
+        resource: &mut Resource,
+        config: &Config,
+    ) -> anyhow::Result<Option<Line>> {
+        let suggestions = Suggestions::new(resource, self.mode)?;
+
+        let headers: Vec<String> = resource.headers().iter().map(|v| v.to_string()).collect();
+
+        crate::wout!(
+            "Press Enter to accept the [default], Tab to complete the value (e.g. Gro<Tab>) or end it \
+             with ? to list the options"
+        );
+
+        let mut editor = Editor::with_config(
+            rustyline::Config::builder()
+                .completion_type(CompletionType::List)
+                .auto_add_history(false)
+                .build(),
+        )?;
+
+        let mut values: Vec<String> = Vec::new();
+        let mut investment = false;
+
+        for name in headers.iter() {
+            let value = match name.as_str() {
+                // Managed by other commands (`export`, `link` and `transfer`).
+                "Exported" | "Transfer" => String::new(),
+                _ => {
+                    let default = match name.as_str() {
+                        "Date" => Some(Date::today().to_string()),
+                        "Currency" => {
+                            let account = headers
+                                .iter()
+                                .position(|name| name == "Account")
+                                .and_then(|index| values.get(index));
+
+                            Some(suggestions.currency(account, config))
+                        }
+                        _ => None,
+                    };
+
+                    let options = suggestions.get(name);
+
+                    prompt(&mut editor, name, default, &options, investment)?
+                }
+            };
+
+            if name == "Category" && value == config.transfer {
+                return Err(transfer_error(config));
+            }
+
+            if name == "Category" {
+                investment = value == config.investments;
+            }
 
             values.push(value);
         }
 
-        Ok(())
+        let line = Line::build(values.clone(), self.mode)?;
+
+        let mut table = Table::new();
+
+        table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+        for (name, value) in headers.iter().zip(values.iter()) {
+            if !value.is_empty() {
+                table.add_row(Row::new(vec![
+                    Cell::new(name).style_spec("bFB"),
+                    Cell::new(value).style_spec("bFW"),
+                ]));
+            }
+        }
+
+        table.printstd();
+
+        let answer = prompt(
+            &mut editor,
+            "Book it? (y/n)",
+            Some("y".to_string()),
+            &[],
+            false,
+        )?;
+
+        if answer.to_lowercase().starts_with('y') {
+            Ok(Some(line))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Values already present in the file, offered as completions when booking interactively.
+#[derive(Debug, Default)]
+struct Suggestions {
+    values: HashMap<&'static str, BTreeSet<String>>,
+    /// Currency of the last transaction of each account.
+    currencies: HashMap<String, Currency>,
+}
+
+impl Suggestions {
+    const FIELDS: [&'static str; 3] = ["Account", "Category", "Venue"];
+
+    fn new(resource: &mut Resource, mode: crate::Mode) -> anyhow::Result<Suggestions> {
+        let mut suggestions = Self::default();
+
+        if let crate::Mode::Networth = mode {
+            return Ok(suggestions);
+        }
+
+        resource.line(&mut |record| {
+            for name in Suggestions::FIELDS.iter() {
+                let value = match *name {
+                    "Account" => record.account(),
+                    "Category" => record.category(),
+                    _ => record.venue(),
+                };
+
+                if !value.is_empty() {
+                    suggestions.values.entry(name).or_default().insert(value);
+                }
+            }
+
+            suggestions
+                .currencies
+                .insert(record.account(), record.currency());
+
+            Ok(())
+        })?;
+
+        Ok(suggestions)
+    }
+
+    fn get(&self, name: &str) -> Vec<String> {
+        self.values
+            .get(name)
+            .map(|values| values.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The usual currency of the account (or the default one).
+    fn currency(&self, account: Option<&String>, config: &Config) -> String {
+        account
+            .and_then(|account| self.currencies.get(account))
+            .map(|currency| currency.code())
+            .unwrap_or_else(|| config.currency.to_string())
+    }
+}
+
+// Both legs of a transfer share an identifier, which is how they are paired, so a leg booked on
+// its own (without one) would break the reports and exports until it is linked.
+fn unlinked_transfer(line: &Line, config: &Config) -> anyhow::Result<()> {
+    if let Line::Transaction(_) = line {
+        if line.category() == config.transfer && line.transfer().is_empty() {
+            return Err(transfer_error(config));
+        }
+    }
+
+    Ok(())
+}
+
+fn transfer_error(config: &Config) -> anyhow::Error {
+    anyhow!(
+        "Lines of the category '{}' are booked with `ledger transfer`, which books both legs",
+        config.transfer
+    )
+}
+
+/// Completion of the values already in the file (the whole value, which may have spaces).
+struct Completion {
+    options: Vec<String>,
+}
+
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok((0, matches(&self.options, &line[..pos])))
+    }
+}
+
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+impl Highlighter for Completion {}
+
+impl Validator for Completion {}
+
+impl Helper for Completion {}
+
+/// Ask for a value until a valid one is entered.
+fn prompt(
+    editor: &mut Editor<Completion, DefaultHistory>,
+    name: &str,
+    default: Option<String>,
+    options: &[String],
+    investment: bool,
+) -> anyhow::Result<String> {
+    editor.set_helper(Some(Completion {
+        options: options.to_vec(),
+    }));
+
+    loop {
+        let label = match &default {
+            Some(value) => format!("{} [{}]: ", name, value),
+            None => format!("{}: ", name),
+        };
+
+        let input = match editor.readline(&label) {
+            Ok(input) => input,
+            Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => {
+                return Err(anyhow!("Booking was interrupted"))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let value = if let Some(prefix) = input.strip_suffix('?') {
+            for option in matches(options, prefix) {
+                crate::wout!("  {}", option);
+            }
+
+            continue;
+        } else if input.trim().is_empty() {
+            default.clone().unwrap_or_default()
+        } else {
+            input.trim().to_string()
+        };
+
+        match validate(name, &value, investment) {
+            Ok(()) => return Ok(value),
+            Err(e) => crate::wout!("  {}", e),
+        }
+    }
+}
+
+fn matches(options: &[String], prefix: &str) -> Vec<String> {
+    let prefix = prefix.trim().to_lowercase();
+
+    options
+        .iter()
+        .filter(|option| option.to_lowercase().starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+// The quantity of investments is the number of shares, which is needed for the networth.
+fn validate(name: &str, value: &str, investment: bool) -> anyhow::Result<()> {
+    match name {
+        "Quantity" if investment && value.parse::<i64>().is_err() => Err(anyhow!(
+            "The quantity of an investment must be a whole number (found '{}')",
+            value
+        )),
+        "Account" | "Category" | "Date" | "Amount" | "Invested" | "Investment"
+            if value.is_empty() =>
+        {
+            Err(anyhow!("{} is required", name))
+        }
+        "Date" => Date::parse(value).map(|_| ()),
+        "Currency" => Currency::parse(value).map(|_| ()),
+        "Amount" | "Invested" | "Investment" => value
+            .parse::<f64>()
+            .map(|_| ())
+            .map_err(|_| anyhow!("Invalid amount: '{}' (e.g. -10.50)", value)),
+        _ => Ok(()),
     }
 }
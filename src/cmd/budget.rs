@@ -0,0 +1,166 @@
+use clap::Parser;
+use prettytable::{format, Cell, Row, Table};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::entity::budget;
+use crate::entity::money::{Currency, Money};
+use crate::entity::report::general;
+use crate::entity::total::Total;
+use crate::exchange::Exchange;
+use crate::filter::{Filter, Period};
+use crate::output::{self, Format, Render, Section};
+use crate::util;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub period: Period,
+    /// Display entries on the same currency (format ISO 4217)
+    #[arg(short = 'C', long)]
+    pub currency: Option<String>,
+}
+
+pub fn run(args: Args, format: Format) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.generate(&config, format)
+}
+
+impl Args {
+    fn generate(&self, config: &Config, format: Format) -> anyhow::Result<()> {
+        let exchange = Exchange::new(config)?;
+
+        let filter = Filter::for_period(&self.period, config);
+
+        let mut total = Total::new(self.currency.as_ref(), config, filter.end)?;
+
+        let currency = util::currency(self.currency.as_ref(), config)?;
+
+        let report = general::Report::new(currency, &mut total, config, &exchange, &filter)?;
+
+        let report = Report::new(&report)?;
+
+        output::print(format, &[("budget", &report)])
+    }
+}
+
+/// Budget of each category next to what was spent on it.
+#[derive(Debug, Serialize)]
+struct Report {
+    currency: Currency,
+    items: Vec<Item>,
+    total: Item,
+}
+
+#[derive(Debug, Serialize)]
+struct Item {
+    category: String,
+    budget: Money,
+    actual: Money,
+    remaining: Money,
+}
+
+impl Report {
+    fn new(report: &general::Report) -> anyhow::Result<Report> {
+        let currency = report.currency();
+
+        let items = report.items();
+
+        let mut planned = Money::new(currency, 0);
+        let mut spent = Money::new(currency, 0);
+
+        let mut result = Vec::new();
+
+        for (category, amount) in report.budgets().values() {
+            let actual = items
+                .iter()
+                .find(|item| item.category().to_uppercase() == category.to_uppercase())
+                .map(|item| item.value())
+                .unwrap_or_else(|| Money::new(currency, 0));
+
+            planned = planned.checked_add(*amount)?;
+            spent = spent.checked_add(actual.abs())?;
+
+            result.push(Item::new(category, *amount, actual)?);
+        }
+
+        Ok(Self {
+            currency,
+            items: result,
+            total: Item::new("Total", planned, spent)?,
+        })
+    }
+}
+
+impl Item {
+    fn new(category: &str, budget: Money, actual: Money) -> crate::Result<Item> {
+        Ok(Self {
+            category: category.to_string(),
+            budget,
+            actual: actual.abs(),
+            remaining: budget::remaining(budget, actual)?,
+        })
+    }
+
+    fn row(&self, style: &str) -> Row {
+        Row::new(vec![
+            Cell::new(&self.category).style_spec(style),
+            Cell::new(&format!("{}", self.budget)).style_spec(style),
+            Cell::new(&format!("{}", self.actual)).style_spec(style),
+            util::money_cell(&self.remaining, true, false, format::Alignment::LEFT),
+            util::consumption_cell(&self.actual, &self.budget, format::Alignment::LEFT),
+        ])
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.category.to_string(),
+            self.budget.to_storage(),
+            self.actual.to_storage(),
+            self.remaining.to_storage(),
+        ]
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Budget", &["Category", "Budget", "Actual", "Remaining"]);
+
+        for item in self.items.iter() {
+            section.rows.push(item.values());
+        }
+
+        section.rows.push(self.total.values());
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        let mut table = Table::new();
+
+        table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+        table.set_titles(Row::new(vec![Cell::new("Budget")
+            .with_hspan(5)
+            .style_spec("bcFC")]));
+
+        table.add_row(Row::new(vec![
+            Cell::new("Category").style_spec("bcFB"),
+            Cell::new("Budget").style_spec("bFB"),
+            Cell::new("Actual").style_spec("bFB"),
+            Cell::new("Remaining").style_spec("bFB"),
+            Cell::new("(%)").style_spec("bFB"),
+        ]));
+
+        for item in self.items.iter() {
+            table.add_row(item.row("bFW"));
+        }
+
+        table.add_row(self.total.row("bFY"));
+
+        table.printstd();
+
+        Ok(())
+    }
+}
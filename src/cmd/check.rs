@@ -0,0 +1,37 @@
+use anyhow::anyhow;
+use clap::Parser;
+
+use crate::config::Config;
+use crate::entity::report::integrity;
+use crate::exchange::Exchange;
+use crate::output::{self, Format};
+
+#[derive(Parser, Debug)]
+pub struct Args {}
+
+pub fn run(args: Args, format: Format) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.check(&config, format)
+}
+
+impl Args {
+    fn check(&self, config: &Config, format: Format) -> anyhow::Result<()> {
+        let exchange = Exchange::new(config)?;
+
+        let report = integrity::Report::new(config, &exchange)?;
+
+        if report.issues().is_empty() {
+            // Other formats are written anyway, so that there is always something to parse.
+            if !matches!(format, Format::Table) {
+                output::print(format, &[("check", &report)])?;
+            }
+
+            return Ok(());
+        }
+
+        output::print(format, &[("check", &report)])?;
+
+        Err(anyhow!("Found {} problem(s)", report.issues().len()))
+    }
+}
@@ -0,0 +1,29 @@
+use clap::Parser;
+
+use crate::config::Config;
+use crate::crypto::{self, Kind};
+use crate::resource::Resource;
+
+#[derive(Parser, Debug)]
+pub struct Args {}
+
+pub fn run(_args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    for (mode, filepath) in config.filepaths() {
+        if crypto::kind(&filepath)? == Kind::Plaintext {
+            crate::wout!("{} is not encrypted", filepath);
+            continue;
+        }
+
+        Resource::new(&config, mode)?.decrypt()?;
+
+        crate::wout!("Decrypted {}", filepath);
+    }
+
+    crate::wout!(
+        "Remove the password from the configuration to keep using the files in plain text"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use clap::Parser;
+
+use crate::config::Config;
+use crate::crypto::{self, Kind};
+use crate::resource::Resource;
+
+#[derive(Parser, Debug)]
+pub struct Args {}
+
+pub fn run(_args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    let mut plaintext = Vec::new();
+
+    for (mode, filepath) in config.filepaths() {
+        if crypto::kind(&filepath)? != Kind::Plaintext {
+            crate::wout!("{} is already encrypted", filepath);
+            continue;
+        }
+
+        plaintext.push((mode, filepath));
+    }
+
+    // Only asked for when there is something to encrypt.
+    if !plaintext.is_empty() {
+        config.confirm_pass()?;
+    }
+
+    for (mode, filepath) in plaintext {
+        let mut resource = Resource::new(&config, mode)?;
+
+        resource.encrypt()?;
+
+        crate::wout!("Encrypted {}", filepath);
+
+        if let Some(pass) = config.pass()? {
+            reseal_backups(&resource, &pass)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Encrypt the backups of the file with the password it is now encrypted with, telling which
+/// ones could not be (`rekey` too).
+pub fn reseal_backups(resource: &Resource, password: &str) -> anyhow::Result<()> {
+    let (count, skipped) = resource.reseal_backups(password)?;
+
+    if count > 0 {
+        crate::wout!(
+            "Encrypted {} backup(s) of {} with the same password",
+            count,
+            resource.filepath
+        );
+    }
+
+    for path in skipped {
+        crate::wout!(
+            "{} could not be opened with the previous password and was left as it was (remove \
+             it if it should not be kept)",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
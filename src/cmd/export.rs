@@ -1,39 +1,68 @@
-use clap::Parser;
-use std::collections::{HashMap, HashSet};
+use anyhow::anyhow;
+use clap::{Parser, ValueEnum};
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
 use crate::config::Config;
 use crate::entity::date::Date;
 use crate::entity::line::{Line, Liner};
 use crate::entity::money::Currency;
+use crate::entity::transfer::Transfers;
 use crate::exchange::Exchange;
+use crate::export::journal::Journal;
+use crate::export::Exported;
+use crate::export::profile::{Fields, Profile};
 use crate::resource::Resource;
 use crate::Mode;
 
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Directory where per-account CSV files will be written
+    /// Directory where the CSV files will be written
     #[clap(short, long, default_value = ".")]
     output: String,
+    /// Format of the export
+    #[clap(short, long, value_enum, default_value_t)]
+    target: Target,
+    /// Profile of the configuration with the format of the CSV files (by default, Date, Payee,
+    /// Notes, Category and Amount in the default currency, one file per account)
+    #[clap(short, long)]
+    profile: Option<String>,
 }
 
-struct ExportRow {
-    date: String,
-    payee: String,
-    notes: String,
-    category: String,
-    amount: String,
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum Target {
+    /// CSV files per account, with the transactions not exported yet
+    #[default]
+    Csv,
+    /// A journal of hledger/ledger-cli (ledger.journal, unless the output is a file), with all
+    /// the transactions
+    Journal,
 }
 
 pub fn run(args: Args) -> anyhow::Result<()> {
     let config = Config::new()?;
     let exchange = Exchange::new(&config)?;
+
+    if let Target::Journal = args.target {
+        return journal(&args, &config, &exchange);
+    }
+
+    let profile = args.profile(&config)?;
+    profile.validate()?;
+
     let default_currency = Currency::parse(&config.currency)?;
     let mut resource = Resource::new(&config, Mode::Ledger)?;
 
-    let mut exports: HashMap<String, Vec<ExportRow>> = HashMap::new();
+    let mut exports: BTreeMap<String, Vec<Vec<String>>> = BTreeMap::new();
     let mut marked: HashSet<usize> = HashSet::new();
-    let mut pending_transfer: Option<(usize, Line)> = None;
+    let mut marked_transfers: HashSet<String> = HashSet::new();
+    // With a profile, what is exported is kept per profile instead of in the Exported column.
+    let mut state = match &args.profile {
+        Some(name) => Some(Exported::load(name)?),
+        None => None,
+    };
+    let mut pending: Vec<String> = Vec::new();
+    let mut transfers = Transfers::new();
     let mut index = 0usize;
 
     resource.line(&mut |record| {
@@ -41,138 +70,161 @@ pub fn run(args: Args) -> anyhow::Result<()> {
         index += 1;
 
         if record.category() == config.transfer {
-            match pending_transfer.take() {
-                None => {
-                    if record.exported().is_empty() {
-                        pending_transfer = Some((current, record.clone()));
+            if let Some(pair) = transfers.pair(record, &exchange) {
+                let key = Exported::transfer_key(&pair.id());
+
+                let exported = match &state {
+                    Some(state) => state.contains(&key),
+                    None => {
+                        !(pair.source.exported().is_empty()
+                            && pair.destination.exported().is_empty())
                     }
-                }
-                Some((prev_idx, prev)) => {
-                    if record.exported().is_empty() {
-                        let (source, source_idx, dest) = if prev.amount().negative() {
-                            (prev, prev_idx, record.clone())
-                        } else {
-                            (record.clone(), current, prev)
-                        };
-                        let dest_idx =
-                            if source_idx == prev_idx { current } else { prev_idx };
-
-                        exports
-                            .entry(source.account())
-                            .or_default()
-                            .push(ExportRow {
-                                date: source.date().to_string(),
-                                payee: dest.account(),
-                                notes: build_notes(&source),
-                                category: source.category(),
-                                amount: format_amount(&source, default_currency, &exchange)?,
-                            });
-
-                        marked.insert(source_idx);
-                        marked.insert(dest_idx);
+                };
+
+                if !exported {
+                    let source = &pair.source;
+
+                    let fields = Fields {
+                        payee: pair.destination.account(),
+                        ..fields(source)
+                    };
+
+                    exports
+                        .entry(profile.filename(&source.account()))
+                        .or_default()
+                        .push(profile.row(&fields, default_currency, &exchange)?);
+
+                    match state {
+                        Some(_) => pending.push(key),
+                        None => {
+                            marked_transfers.insert(pair.id());
+                        }
                     }
                 }
             }
         } else {
-            pending_transfer = None;
+            let key = state
+                .as_mut()
+                .map(|state| state.key(Mode::Ledger, record));
 
-            if record.exported().is_empty() {
+            let exported = match (&state, &key) {
+                (Some(state), Some(key)) => state.contains(key),
+                _ => !record.exported().is_empty(),
+            };
+
+            if !exported {
                 exports
-                    .entry(record.account())
+                    .entry(profile.filename(&record.account()))
                     .or_default()
-                    .push(ExportRow {
-                        date: record.date().to_string(),
-                        payee: record.venue(),
-                        notes: build_notes(record),
-                        category: record.category(),
-                        amount: format_amount(record, default_currency, &exchange)?,
-                    });
-
-                marked.insert(current);
+                    .push(profile.row(&fields(record), default_currency, &exchange)?);
+
+                match key {
+                    Some(key) => pending.push(key),
+                    None => {
+                        marked.insert(current);
+                    }
+                }
             }
         }
 
         Ok(())
     })?;
 
+    // Networth entries
+    let mut nw_resource = Resource::new(&config, Mode::Networth)?;
+    let mut nw_marked: HashSet<usize> = HashSet::new();
+    let mut nw_index = 0usize;
+    let mut previous_investment: Option<crate::entity::money::Money> = None;
+
+    if profile.networth {
+        nw_resource.line(&mut |record| {
+            let current = nw_index;
+            nw_index += 1;
+
+            let prev = previous_investment;
+            previous_investment = Some(record.investment());
+
+            let key = state
+                .as_mut()
+                .map(|state| state.key(Mode::Networth, record));
+
+            let exported = match (&state, &key) {
+                (Some(state), Some(key)) => state.contains(key),
+                _ => !record.exported().is_empty(),
+            };
+
+            if !exported {
+                let zero = crate::entity::money::Money::new(record.currency(), 0);
+                let delta = record.investment().checked_sub(prev.unwrap_or(zero))?;
+
+                let fields = Fields {
+                    account: record.account(),
+                    date: record.date(),
+                    category: record.category(),
+                    amount: delta,
+                    notes: "Daily".to_string(),
+                    ..Default::default()
+                };
+
+                exports
+                    .entry(profile.filename(&record.account()))
+                    .or_default()
+                    .push(profile.row(&fields, default_currency, &exchange)?);
+
+                match key {
+                    Some(key) => pending.push(key),
+                    None => {
+                        nw_marked.insert(current);
+                    }
+                }
+            }
+
+            Ok(())
+        })?;
+    }
+
     let output_dir = Path::new(&args.output);
 
-    for (account, rows) in &exports {
-        let path = output_dir.join(format!("{}.csv", sanitize(account)));
+    for (filename, rows) in &exports {
+        let path = output_dir.join(filename);
         let mut wrt = csv::Writer::from_path(&path)?;
-        wrt.write_record(["Date", "Payee", "Notes", "Category", "Amount"])?;
+        wrt.write_record(profile.headers())?;
         for row in rows {
-            wrt.write_record([
-                &row.date, &row.payee, &row.notes, &row.category, &row.amount,
-            ])?;
+            wrt.write_record(row)?;
         }
         wrt.flush()?;
     }
 
-    let today = Date::today().to_string();
-    let mut rewrite_index = 0usize;
-
-    resource.rewrite(&mut |record| {
-        let current = rewrite_index;
-        rewrite_index += 1;
-
-        if marked.contains(&current) {
-            record.set_exported(today.clone());
+    if let Some(state) = state.as_mut().filter(|_| profile.mark && !pending.is_empty()) {
+        for key in pending {
+            state.insert(key);
         }
 
-        Ok(vec![record.clone()])
-    })?;
+        state.store()?;
+    }
 
-    // Networth entries
-    let mut nw_resource = Resource::new(&config, Mode::Networth)?;
-    let mut nw_exports: HashMap<String, Vec<ExportRow>> = HashMap::new();
-    let mut nw_marked: HashSet<usize> = HashSet::new();
-    let mut nw_index = 0usize;
-    let mut previous_investment: Option<crate::entity::money::Money> = None;
+    let today = Date::today().to_string();
 
-    nw_resource.line(&mut |record| {
-        let current = nw_index;
-        nw_index += 1;
-
-        let prev = previous_investment;
-        previous_investment = Some(record.investment());
-
-        if record.exported().is_empty() {
-            let zero = crate::entity::money::Money::new(record.currency(), 0);
-            let delta = record.investment() - prev.unwrap_or(zero);
-            let amount = delta.exchange(default_currency, &exchange)?;
-            let prec = default_currency.decimal_places() as usize;
-
-            nw_exports
-                .entry(record.account())
-                .or_default()
-                .push(ExportRow {
-                    date: record.date().to_string(),
-                    payee: String::new(),
-                    notes: "Daily".to_string(),
-                    category: record.category(),
-                    amount: format!("{:.prec$}", amount.to_number()),
-                });
+    if profile.mark && !(marked.is_empty() && marked_transfers.is_empty()) {
+        let mut rewrite_index = 0usize;
 
-            nw_marked.insert(current);
-        }
+        resource.rewrite(&mut |record| {
+            let current = rewrite_index;
+            rewrite_index += 1;
 
-        Ok(())
-    })?;
+            let transfer = record.category() == config.transfer;
 
-    for (account, rows) in &nw_exports {
-        let path = output_dir.join(format!("{}.csv", sanitize(account)));
-        let mut wrt = csv::Writer::from_path(&path)?;
-        wrt.write_record(["Date", "Payee", "Notes", "Category", "Amount"])?;
-        for row in rows {
-            wrt.write_record([
-                &row.date, &row.payee, &row.notes, &row.category, &row.amount,
-            ])?;
-        }
-        wrt.flush()?;
+            if marked.contains(&current)
+                || (transfer && marked_transfers.contains(&record.transfer()))
+            {
+                record.set_exported(today.clone());
+            }
+
+            Ok(vec![record.clone()])
+        })?;
     }
 
-    if !nw_marked.is_empty() {
+    if profile.mark && !nw_marked.is_empty() {
         let mut nw_rewrite_index = 0usize;
         nw_resource.rewrite(&mut |record| {
             let current = nw_rewrite_index;
@@ -184,22 +236,75 @@ pub fn run(args: Args) -> anyhow::Result<()> {
         })?;
     }
 
-    let total: usize = exports.values().map(|v| v.len()).sum::<usize>()
-        + nw_exports.values().map(|v| v.len()).sum::<usize>();
+    let total: usize = exports.values().map(|v| v.len()).sum::<usize>();
 
     if total == 0 {
         println!("Nothing to export.");
     } else {
-        let accounts = exports.len() + nw_exports.len();
         println!(
-            "Exported {} transaction(s) across {} account(s).",
-            total, accounts
+            "Exported {} transaction(s) to {} file(s).",
+            total,
+            exports.len()
         );
     }
 
     Ok(())
 }
 
+impl Args {
+    // Without profiles in the configuration (or one selected), the default format is used.
+    fn profile(&self, config: &Config) -> anyhow::Result<Profile> {
+        match &self.profile {
+            Some(name) => config
+                .export
+                .profiles
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("There is no export profile '{}'", name)),
+            None => Ok(Profile::default()),
+        }
+    }
+}
+
+fn journal(args: &Args, config: &Config, exchange: &Exchange) -> anyhow::Result<()> {
+    let journal = Journal::new(config, exchange)?;
+
+    let output = Path::new(&args.output);
+
+    let path = if output.is_dir() {
+        output.join("ledger.journal")
+    } else {
+        output.to_path_buf()
+    };
+
+    journal.write(&path)?;
+
+    println!(
+        "Exported {} transaction(s) to {}.",
+        journal.len(),
+        path.display()
+    );
+
+    Ok(())
+}
+
+fn fields(line: &Line) -> Fields {
+    Fields {
+        account: line.account(),
+        date: line.date(),
+        category: line.category(),
+        description: line.description(),
+        quantity: line.quantity(),
+        venue: line.venue(),
+        amount: line.amount(),
+        trip: line.trip(),
+        transfer: line.transfer(),
+        payee: line.venue(),
+        notes: build_notes(line),
+        exported: line.exported(),
+    }
+}
+
 fn build_notes(line: &Line) -> String {
     let desc = line.description();
     let qty = line.quantity();
@@ -209,15 +314,3 @@ fn build_notes(line: &Line) -> String {
         format!("{} [{}]", desc, qty)
     }
 }
-
-fn format_amount(line: &Line, currency: Currency, exchange: &Exchange) -> anyhow::Result<String> {
-    let money = line.amount().exchange(currency, exchange)?;
-    let prec = currency.decimal_places() as usize;
-    Ok(format!("{:.prec$}", money.to_number()))
-}
-
-fn sanitize(name: &str) -> String {
-    name.chars()
-        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
-        .collect()
-}
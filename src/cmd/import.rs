@@ -0,0 +1,142 @@
+use anyhow::anyhow;
+use clap::Parser;
+use prettytable::{format, Cell, Row, Table};
+
+use crate::config::Config;
+use crate::entity::line::{Line, Liner};
+use crate::import::{delimited, ofx, Duplicates, Imported, Rules};
+use crate::resource::Resource;
+use crate::Mode;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Statement of the bank (CSV, or OFX/QFX by the extension)
+    file: String,
+    /// Profile of the configuration with the format of the statement (only needed when there is
+    /// more than one)
+    #[arg(short, long)]
+    profile: Option<String>,
+    /// Display the transactions that would be imported without booking them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.import(&config)
+}
+
+impl Args {
+    fn import(&self, config: &Config) -> anyhow::Result<()> {
+        let rules = Rules::new(&config.import.rules)?;
+
+        let records = if ofx::matches(&self.file) {
+            ofx::read(&self.file, config)?
+        } else {
+            self.profile(config)?.read(&self.file, config)?
+        };
+
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        let mut duplicates = Duplicates::new(&resource.lines()?);
+
+        let mut imported = Imported::load()?;
+
+        let mut lines = Vec::new();
+        let mut skipped = Vec::new();
+
+        for record in records {
+            let known = imported.contains(&record);
+
+            imported.insert(&record);
+
+            let line = rules.apply(record)?;
+
+            // Even when imported before, the line still matches one in the ledger (unless it was
+            // removed), which must not be matched by another line.
+            if duplicates.take(&line) || known {
+                skipped.push(line);
+            } else {
+                lines.push(line);
+            }
+        }
+
+        Args::display(&lines, &skipped);
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        lines.sort();
+
+        if !lines.is_empty() {
+            resource.book(&lines)?;
+        }
+
+        imported.store()?;
+
+        crate::wout!(
+            "Imported {} transaction(s), skipped {} already imported or in the ledger.",
+            lines.len(),
+            skipped.len()
+        );
+
+        Ok(())
+    }
+
+    fn profile<'a>(&self, config: &'a Config) -> anyhow::Result<&'a delimited::Profile> {
+        let profiles = &config.import.profiles;
+
+        match &self.profile {
+            Some(name) => profiles
+                .get(name)
+                .ok_or_else(|| anyhow!("There is no import profile '{}'", name)),
+            None if profiles.len() == 1 => Ok(profiles.values().next().unwrap()),
+            None if profiles.is_empty() => Err(anyhow!("There are no import profiles configured")),
+            None => Err(anyhow!(
+                "Select one of the import profiles with --profile ({})",
+                profiles.keys().cloned().collect::<Vec<String>>().join(", ")
+            )),
+        }
+    }
+
+    fn display(lines: &[Line], skipped: &[Line]) {
+        let mut table = Table::new();
+
+        table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+        table.set_titles(Row::new(vec![Cell::new("Import")
+            .with_hspan(7)
+            .style_spec("bcFC")]));
+
+        table.add_row(Row::new(vec![
+            Cell::new("Date").style_spec("bcFB"),
+            Cell::new("Account").style_spec("bcFB"),
+            Cell::new("Category").style_spec("bcFB"),
+            Cell::new("Description").style_spec("bcFB"),
+            Cell::new("Venue").style_spec("bcFB"),
+            Cell::new("Amount").style_spec("bFB"),
+            Cell::new("Status").style_spec("bFB"),
+        ]));
+
+        let rows = lines
+            .iter()
+            .map(|line| (line, "New", "bFW"))
+            .chain(skipped.iter().map(|line| (line, "Duplicate", "FY")));
+
+        for (line, status, style) in rows {
+            table.add_row(Row::new(vec![
+                Cell::new(&line.date().to_string()).style_spec(style),
+                Cell::new(&line.account()).style_spec(style),
+                Cell::new(&line.category()).style_spec(style),
+                Cell::new(&line.description()).style_spec(style),
+                Cell::new(&line.venue()).style_spec(style),
+                Cell::new(&format!("{}", line.amount())).style_spec(style),
+                Cell::new(status).style_spec(style),
+            ]));
+        }
+
+        table.printstd();
+    }
+}
@@ -0,0 +1,154 @@
+use clap::Parser;
+
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::entity::line::{Line, Liner};
+use crate::entity::transfer;
+use crate::exchange::Exchange;
+use crate::resource::Resource;
+use crate::Mode;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Display the transfers that would be linked without changing the ledger
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+    /// Link them without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.link(&config)
+}
+
+impl Args {
+    fn link(&self, config: &Config) -> anyhow::Result<()> {
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        let exchange = Exchange::new(config)?;
+
+        let mut lines = resource.lines()?;
+
+        let mut existing: HashSet<String> = lines
+            .iter()
+            .map(|line| line.transfer())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        // Transfers booked before they had an identifier were paired by their position, i.e.
+        // each leg was followed by the other one (ignoring any other transaction in between).
+        let unlinked: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.category() == config.transfer && line.transfer().is_empty())
+            .map(|(index, _)| index)
+            .collect();
+
+        // A leg is only paired with the next one when they balance, so that a missing (or extra)
+        // leg is left out instead of shifting every pair after it.
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        let mut left: Vec<usize> = Vec::new();
+
+        let mut position = 0;
+
+        while position < unlinked.len() {
+            let first = unlinked[position];
+
+            match unlinked.get(position + 1) {
+                Some(&second) if balanced(&lines[first], &lines[second], &exchange) => {
+                    pairs.push((first, second));
+                    position += 2;
+                }
+                _ => {
+                    left.push(first);
+                    position += 1;
+                }
+            }
+        }
+
+        for (first, second) in pairs.iter() {
+            crate::wout!(
+                "{} {} {} → {} {} {}",
+                lines[*first].date(),
+                lines[*first].account(),
+                lines[*first].amount(),
+                lines[*second].date(),
+                lines[*second].account(),
+                lines[*second].amount()
+            );
+        }
+
+        for leg in left.iter() {
+            crate::wout!(
+                "The transfer of '{}' on {} ({}) has no counterpart that balances it, so it is \
+                 left without an identifier",
+                lines[*leg].account(),
+                lines[*leg].date(),
+                lines[*leg].amount()
+            );
+        }
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        if pairs.is_empty() {
+            crate::wout!("Linked 0 transfer(s).");
+            return Ok(());
+        }
+
+        if !(self.yes || confirm()?) {
+            crate::wout!("Nothing was linked");
+            return Ok(());
+        }
+
+        for (first, second) in pairs.iter() {
+            let id = transfer::identifier(lines[*first].date(), &existing);
+
+            lines[*first].set_transfer(id.to_string());
+            lines[*second].set_transfer(id.to_string());
+
+            existing.insert(id);
+        }
+
+        resource.create_with(lines)?;
+
+        crate::wout!("Linked {} transfer(s).", pairs.len());
+
+        Ok(())
+    }
+}
+
+// Whether the legs have opposite signs and validate as a transfer (with any identifier).
+fn balanced(first: &Line, second: &Line, exchange: &Exchange) -> bool {
+    if first.amount().negative() == second.amount().negative() {
+        return false;
+    }
+
+    let mut legs = [first.clone(), second.clone()];
+
+    for leg in legs.iter_mut() {
+        leg.set_transfer("link".to_string());
+    }
+
+    let mut transfers = transfer::Transfers::new();
+
+    transfers.add(&legs[0], exchange);
+
+    let added = transfers.add(&legs[1], exchange);
+
+    added.pair.is_some() && added.problem.is_none()
+}
+
+fn confirm() -> anyhow::Result<bool> {
+    crate::wout!("Link them? [y/N]");
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
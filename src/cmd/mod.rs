@@ -1,11 +1,22 @@
+pub mod agent;
 pub mod balance;
 pub mod export;
 pub mod book;
+pub mod budget;
+pub mod check;
 pub mod configure;
 pub mod convert;
 pub mod create;
+pub mod decrypt;
 pub mod edit;
+pub mod encrypt;
+pub mod import;
+pub mod link;
 pub mod networth;
+pub mod recur;
+pub mod rekey;
 pub mod report;
+pub mod restore;
 pub mod show;
 pub mod sort;
+pub mod transfer;
@@ -3,6 +3,8 @@ use clap::Parser;
 use crate::config::Config;
 use crate::entity::report::networth;
 use crate::exchange::Exchange;
+use crate::output::{self, Format};
+use crate::price::Prices;
 use crate::util;
 
 #[derive(Parser, Debug)]
@@ -13,26 +15,31 @@ pub struct Args {
     /// Save the total networth to the networth CSV
     #[arg(short, long)]
     save: bool,
+    /// Ignore the cached prices of the investments and fetch them again
+    #[arg(short, long)]
+    refresh: bool,
 }
 
-pub fn run(args: Args) -> anyhow::Result<()> {
+pub fn run(args: Args, format: Format) -> anyhow::Result<()> {
     let config = Config::new()?;
 
-    args.generate(config)
+    args.generate(config, format)
 }
 
 impl Args {
-    fn generate(&self, config: Config) -> anyhow::Result<()> {
+    fn generate(&self, config: Config, format: Format) -> anyhow::Result<()> {
         let exchange = Exchange::new(&config)?;
 
         let currency = util::currency(self.currency.as_ref(), &config)?;
 
-        let report = networth::Report::new(config, exchange, currency)?;
+        let mut prices = Prices::new(&config, self.refresh)?;
+
+        let report = networth::Report::new(config, exchange, &mut prices, currency)?;
 
         if self.save {
             report.save()?
         } else {
-            report.display()
+            output::print(format, &[("networth", &report)])?
         };
 
         Ok(())
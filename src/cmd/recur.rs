@@ -0,0 +1,112 @@
+use anyhow::anyhow;
+use clap::Parser;
+
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::entity::recurring::{Recurring, State};
+use crate::resource::Resource;
+use crate::Mode;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Book the occurrences until the date (by default, today)
+    #[arg(short, long)]
+    until: Option<Date>,
+    /// Display the occurrences due without booking them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.recur(&config)
+}
+
+impl Args {
+    // Each definition is booked since its last occurrence booked, but lines already in the ledger
+    // are skipped too, so that running it more than once (or losing the state) has no effect.
+    fn recur(&self, config: &Config) -> anyhow::Result<()> {
+        let path = config.recurring_path()?;
+
+        let definitions = Recurring::load(&path)?;
+
+        if definitions.is_empty() {
+            crate::wout!("There are no recurring transactions defined in '{}'", path);
+            return Ok(());
+        }
+
+        if let Some(definition) = definitions.iter().find(|v| v.category == config.transfer) {
+            return Err(anyhow!(
+                "'{}' is a transfer, which cannot be booked as a recurring transaction",
+                definition.name
+            ));
+        }
+
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        let existing: HashSet<String> = resource.lines()?.iter().map(Args::key).collect();
+
+        let mut state = State::load()?;
+
+        let until = self.until.unwrap_or_else(Date::today);
+
+        let mut lines = Vec::new();
+
+        for definition in definitions.iter() {
+            let dates = definition.occurrences(state.booked.get(&definition.name).copied(), until);
+
+            for date in dates.iter() {
+                let line = definition.line(*date)?;
+
+                if !existing.contains(&Args::key(&line)) {
+                    lines.push(line);
+                }
+            }
+
+            if let Some(last) = dates.last() {
+                state.booked.insert(definition.name.to_string(), *last);
+            }
+        }
+
+        lines.sort();
+
+        if self.dry_run {
+            for line in lines.iter() {
+                crate::wout!(
+                    "{} {} {} {}",
+                    line.date(),
+                    line.account(),
+                    line.category(),
+                    line.amount()
+                );
+            }
+
+            return Ok(());
+        }
+
+        if !lines.is_empty() {
+            resource.book(&lines)?;
+        }
+
+        state.store()?;
+
+        crate::wout!("Booked {} transaction(s).", lines.len());
+
+        Ok(())
+    }
+
+    fn key(line: &Line) -> String {
+        format!(
+            "{}|{}|{}|{}|{}",
+            line.account(),
+            line.date(),
+            line.category(),
+            line.description(),
+            line.amount().to_storage()
+        )
+    }
+}
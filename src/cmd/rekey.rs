@@ -0,0 +1,101 @@
+use anyhow::anyhow;
+use clap::Parser;
+use tempfile::NamedTempFile;
+
+use crate::config::Config;
+use crate::password;
+use crate::resource::Resource;
+use crate::util;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Generate a random password instead of asking for one
+    #[arg(short, long)]
+    generate: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let mut config = Config::new()?;
+
+    // Both files are locked and encrypted with the new password before any of them is replaced,
+    // and the ones replaced are put back if a later one fails, so that the files always share
+    // the same password.
+    let mut resources: Vec<Resource> = Vec::new();
+
+    for (mode, _) in config.filepaths() {
+        resources.push(Resource::new(&config, mode)?);
+    }
+
+    // Only after the current password is known (it might be asked for too).
+    let password = if args.generate {
+        util::random_pass()
+    } else {
+        password::confirm("New password")?
+    };
+
+    let mut files = Vec::new();
+
+    for resource in resources.iter_mut() {
+        files.push((resource.reencrypt(&password)?, resource.snapshot()?));
+    }
+
+    // Before any file uses it, so that it is not lost whatever happens next.
+    if args.generate {
+        crate::wout!("The new password is {}", password);
+    }
+
+    let mut replaced: Vec<(&Resource, NamedTempFile)> = Vec::new();
+
+    for (resource, (file, original)) in resources.iter().zip(files) {
+        if let Err(e) = resource.replace(file) {
+            return Err(rollback(replaced, e));
+        }
+
+        replaced.push((resource, original));
+    }
+
+    for resource in resources.iter() {
+        crate::wout!("Encrypted {} with the new password", resource.filepath);
+    }
+
+    let stored = config.set_pass(&password).map_err(|e| {
+        anyhow!(
+            "The files are encrypted with the new password, but the configuration could not be \
+             updated ({}), set it as 'encryption' manually",
+            e
+        )
+    })?;
+
+    if !stored {
+        crate::wout!(
+            "Update the source of the password ('password' in the configuration) with the new one"
+        );
+    }
+
+    // Only once the files and the configuration agree on the new password.
+    for resource in resources.iter() {
+        super::encrypt::reseal_backups(resource, &password)?;
+    }
+
+    Ok(())
+}
+
+// Put back the files already encrypted with the new password, as they were.
+fn rollback(replaced: Vec<(&Resource, NamedTempFile)>, error: anyhow::Error) -> anyhow::Error {
+    for (resource, original) in replaced {
+        if let Err(e) = resource.replace(original) {
+            return anyhow!(
+                "{} (and {} could not be put back: {}), so it is encrypted with the new password, \
+                 while the other files still use the current one",
+                error,
+                resource.filepath,
+                e
+            );
+        }
+    }
+
+    anyhow!(
+        "{} (the files are still encrypted with the current password)",
+        error
+    )
+}
@@ -1,25 +1,18 @@
 use clap::Parser;
 
 use crate::config::Config;
+use crate::entity::report::comparison::{self, Granularity};
 use crate::entity::report::{check, general};
 use crate::entity::{date::Date, total::Total};
 use crate::exchange::Exchange;
-use crate::filter::Filter;
+use crate::filter::{Filter, Period};
+use crate::output::{self, Format};
+use crate::util;
 
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Select entries that occurred on the year
-    #[arg(short, long)]
-    pub year: Option<i32>,
-    /// Select entries that occurred on the month
-    #[arg(short, long)]
-    pub month: Option<u32>,
-    /// Select entries that occurred after the date
-    #[arg(short, long)]
-    pub from: Option<Date>,
-    /// Select entries that occurred before the date
-    #[arg(short, long)]
-    pub till: Option<Date>,
+    #[command(flatten)]
+    pub period: Period,
     /// Exclude entries that match the categories
     #[arg(short, long)]
     pub exclude: Vec<String>,
@@ -29,36 +22,70 @@ pub struct Args {
     /// Display report with aggregated information
     #[arg(short, long)]
     check: bool,
+    /// Compare the categories over consecutive months or years
+    #[arg(long, value_enum)]
+    compare: Option<Granularity>,
+    /// Number of periods to compare (up to the one of the selected date or today)
+    #[arg(long, default_value_t = 12)]
+    periods: u32,
 }
 
-pub fn run(args: Args) -> anyhow::Result<()> {
+pub fn run(args: Args, format: Format) -> anyhow::Result<()> {
     let config = Config::new()?;
 
-    args.generate(&config)
+    args.generate(&config, format)
 }
 
 impl Args {
-    fn generate(&self, config: &Config) -> anyhow::Result<()> {
+    fn generate(&self, config: &Config, format: Format) -> anyhow::Result<()> {
         let exchange = Exchange::new(config)?;
 
         let filter = Filter::report(self, config);
 
-        if self.check {
+        if let Some(granularity) = self.compare {
+            let periods = granularity.periods(self.last(), self.periods);
+
+            let filters: Vec<Filter> = periods
+                .iter()
+                .map(|period| Filter::comparison(self, config, period.start, period.end))
+                .collect();
+
+            let currency = util::currency(self.currency.as_ref(), config)?;
+
+            let report = comparison::Report::new(currency, config, &exchange, periods, &filters)?;
+
+            output::print(format, &[("comparison", &report)])?;
+        } else if self.check {
             let report = check::Report::new(config, &filter)?;
 
-            report.display();
+            output::print(format, &[("report", &report)])?;
         } else {
             let mut total = Total::new(self.currency.as_ref(), config, filter.end)?;
 
-            let report = general::Report::new(self, &mut total, config, &exchange, &filter)?;
+            let currency = util::currency(self.currency.as_ref(), config)?;
 
-            let summary = general::Summary::new(&report, total);
+            let report = general::Report::new(currency, &mut total, config, &exchange, &filter)?;
 
-            report.display();
+            let summary = general::Summary::new(&report, total);
 
-            summary.display();
+            output::print(format, &[("report", &report), ("summary", &summary)])?;
         }
 
         Ok(())
     }
+
+    // The last period compared is the one including the date selected (with --till, --year or
+    // --month), or today.
+    fn last(&self) -> Date {
+        let today = Date::today();
+
+        match (self.period.till, self.period.year, self.period.month) {
+            (Some(date), _, _) => date,
+            (None, None, None) => today,
+            (None, year, month) => {
+                Date::from_ymd(year.unwrap_or_else(|| today.year()), month.unwrap_or(12), 1)
+                    .end_of_month()
+            }
+        }
+    }
 }
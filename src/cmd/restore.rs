@@ -0,0 +1,88 @@
+use anyhow::anyhow;
+use clap::Parser;
+use prettytable::{format, Cell, Row, Table};
+
+use crate::backup::{self, Backup};
+use crate::config::Config;
+use crate::resource::Resource;
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Number of the backup to restore, as listed (without it, the backups are listed)
+    backup: Option<usize>,
+    #[arg(
+        value_enum,
+        default_value = "ledger",
+        default_value_if("networth", "true", Some("networth")),
+        hide = true
+    )]
+    mode: crate::Mode,
+    /// Restore networth CSV instead of ledger CSV
+    #[arg(short, long)]
+    networth: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    let filepath = config.filepath(args.mode);
+    let backups = backup::list(&config.backups_path()?, &filepath)?;
+
+    let number = match args.backup {
+        Some(value) => value,
+        None => {
+            display(&filepath, &backups);
+            return Ok(());
+        }
+    };
+
+    let backup = number
+        .checked_sub(1)
+        .and_then(|index| backups.get(index))
+        .ok_or_else(|| anyhow!("There is no backup {} of '{}'", number, filepath))?;
+
+    Resource::new(&config, args.mode)?.restore(&backup.path)?;
+
+    crate::wout!(
+        "Restored {} from {} (the previous version is now the most recent backup)",
+        filepath,
+        backup.time
+    );
+
+    Ok(())
+}
+
+fn display(filepath: &str, backups: &[Backup]) {
+    if backups.is_empty() {
+        crate::wout!("There are no backups of {}", filepath);
+        return;
+    }
+
+    let mut table = Table::new();
+
+    table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+    let title = format!("Backups of {}", filepath);
+
+    table.set_titles(Row::new(vec![Cell::new(&title)
+        .with_hspan(4)
+        .style_spec("bcFC")]));
+
+    table.add_row(Row::new(vec![
+        Cell::new("#").style_spec("bFB"),
+        Cell::new("Time").style_spec("bcFB"),
+        Cell::new("Size").style_spec("bFB"),
+        Cell::new("File").style_spec("bcFB"),
+    ]));
+
+    for (index, backup) in backups.iter().enumerate() {
+        table.add_row(Row::new(vec![
+            Cell::new(&(index + 1).to_string()).style_spec("bFY"),
+            Cell::new(&backup.time.to_string()).style_spec("bFW"),
+            Cell::new(&backup.size.to_string()).style_spec("bFW"),
+            Cell::new(&backup.path.display().to_string()).style_spec("FY"),
+        ]));
+    }
+
+    table.printstd();
+}
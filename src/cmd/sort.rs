@@ -1,7 +1,6 @@
 use clap::Parser;
 
 use crate::config::Config;
-use crate::entity::line::Line;
 use crate::resource::Resource;
 
 #[derive(Parser, Debug)]
@@ -28,12 +27,7 @@ impl Args {
     fn sort(&self, config: &Config) -> anyhow::Result<()> {
         let mut resource = Resource::new(config, self.mode)?;
 
-        let mut lines: Vec<Line> = Vec::new();
-
-        resource.line(&mut |record| {
-            lines.push(record.clone());
-            Ok(())
-        })?;
+        let mut lines = resource.lines()?;
 
         lines.sort();
 
@@ -0,0 +1,147 @@
+use anyhow::anyhow;
+use clap::Parser;
+
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::entity::money::{Currency, Money};
+use crate::entity::transaction::Transaction;
+use crate::entity::transfer::{self, Transfers};
+use crate::exchange::Exchange;
+use crate::resource::Resource;
+use crate::{util, Mode};
+
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Account the money leaves
+    from: String,
+    /// Account the money arrives to
+    to: String,
+    /// Amount leaving the source account
+    amount: String,
+    /// Currency of the amount (format ISO 4217)
+    #[arg(short = 'C', long)]
+    currency: Option<String>,
+    /// Amount arriving to the destination account (by default, the amount exchanged with the
+    /// rates of the date)
+    #[arg(short, long)]
+    target_amount: Option<String>,
+    /// Currency of the destination account (format ISO 4217)
+    #[arg(short = 'T', long)]
+    target_currency: Option<String>,
+    /// Date of the transfer (by default, today)
+    #[arg(short, long)]
+    date: Option<Date>,
+    /// Description of both lines of the transfer
+    #[arg(short = 'D', long, default_value = "")]
+    description: String,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let config = Config::new()?;
+
+    args.transfer(&config)
+}
+
+impl Args {
+    fn transfer(&self, config: &Config) -> anyhow::Result<()> {
+        if self.from == self.to {
+            return Err(anyhow!(
+                "The source and destination accounts must be different"
+            ));
+        }
+
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        let exchange = Exchange::new(config)?;
+
+        let date = self.date.unwrap_or_else(Date::today);
+
+        let currency = util::currency(self.currency.as_ref(), config)?;
+
+        let target_currency = match &self.target_currency {
+            Some(code) => util::currency(Some(code), config)?,
+            None => currency,
+        };
+
+        let amount = Money::parse(&self.amount, currency)?.abs();
+
+        let target = match &self.target_amount {
+            Some(value) => Money::parse(value, target_currency)?.abs(),
+            None => amount.exchange(target_currency, &exchange, date)?,
+        };
+
+        if amount.zero() || target.zero() {
+            return Err(anyhow!("The amount of a transfer cannot be zero"));
+        }
+
+        let existing: HashSet<String> = resource
+            .lines()?
+            .iter()
+            .map(|line| line.transfer())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        let id = transfer::identifier(date, &existing);
+
+        let source = self.leg(config, &self.from, date, -amount.cents(), currency, &id);
+        let destination = self.leg(config, &self.to, date, target.cents(), target_currency, &id);
+
+        // Both legs are validated as any other transfer before booking them.
+        let mut transfers = Transfers::new();
+
+        for leg in [&source, &destination].iter() {
+            if let Some(problem) = transfers.add(leg, &exchange).problem {
+                return Err(problem);
+            }
+        }
+
+        let summary = format!(
+            "{} {} → {} {}",
+            self.from,
+            source.amount(),
+            self.to,
+            destination.amount()
+        );
+
+        resource.book(&[source, destination])?;
+
+        if currency == target_currency {
+            crate::wout!("{}", summary);
+        } else {
+            crate::wout!(
+                "{} (1 {} = {:.4} {})",
+                summary,
+                currency.code(),
+                target.to_number() / amount.to_number(),
+                target_currency.code()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn leg(
+        &self,
+        config: &Config,
+        account: &str,
+        date: Date,
+        cents: i64,
+        currency: Currency,
+        id: &str,
+    ) -> Line {
+        Transaction {
+            account: account.to_string(),
+            date,
+            category: config.transfer.to_string(),
+            description: self.description.to_string(),
+            amount: Money::new(currency, cents),
+            currency,
+            transfer: id.to_string(),
+            ..Default::default()
+        }
+        .into()
+    }
+}
@@ -1,9 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+use crate::crypto;
+use crate::entity::budget::Budget;
+use crate::exchange::provider::Source;
+use crate::exchange::Fallback;
+use crate::export;
+use crate::import;
+use crate::password;
+use crate::price::provider::Source as PriceSource;
 use crate::xdg::Xdg;
 use crate::{util, Mode};
 
@@ -12,11 +22,36 @@ const CONFIGURATION_FILENAME: &str = "config";
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     encryption: Option<String>,
+    /// Sources of the password other than `encryption` (which is stored in plain text)
+    #[serde(default)]
+    password: password::Settings,
+    /// Cost of deriving the key from the password (interactive, moderate or sensitive)
+    #[serde(default)]
+    kdf: crypto::Strength,
+    // The password is only resolved once (e.g. asked for), even if both files are used.
+    #[serde(skip)]
+    resolved: OnceCell<Option<String>>,
     files: Files,
-    exchange_key: String,
+    #[serde(default)]
+    exchange_key: Option<String>,
+    #[serde(default)]
+    pub exchange: ExchangeSettings,
+    #[serde(default)]
+    pub prices: PriceSettings,
     pub transfer: String,
     pub ignored_accounts: Vec<String>,
     pub investments: String,
+    /// Categories expected in the ledger (any category is accepted when empty)
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+    #[serde(default)]
+    pub import: import::Settings,
+    #[serde(default)]
+    pub export: export::Settings,
+    #[serde(default)]
+    pub backups: Backups,
     pub currency: String,
 }
 
@@ -24,6 +59,77 @@ pub struct Config {
 struct Files {
     ledger: String,
     networth: String,
+    #[serde(default)]
+    recurring: Option<String>,
+}
+
+/// Copies of the files kept before they are changed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Backups {
+    /// Number of copies kept of each file (none with 0)
+    #[serde(default = "Backups::default_keep")]
+    pub keep: usize,
+    /// Directory of the copies (`backups` in the configuration directory by default)
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+impl Default for Backups {
+    fn default() -> Self {
+        Self {
+            keep: Self::default_keep(),
+            directory: None,
+        }
+    }
+}
+
+impl Backups {
+    fn default_keep() -> usize {
+        10
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExchangeSettings {
+    #[serde(default)]
+    pub fallback: Fallback,
+    #[serde(default)]
+    pub provider: Source,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceSettings {
+    #[serde(default)]
+    pub default: PriceSource,
+    #[serde(default)]
+    pub assets: BTreeMap<String, PriceSource>,
+    /// Number of prices downloaded at the same time
+    #[serde(default = "PriceSettings::default_workers")]
+    pub workers: usize,
+    /// Maximum time (in seconds) to wait for each price
+    #[serde(default = "PriceSettings::default_timeout")]
+    pub timeout: u64,
+}
+
+impl Default for PriceSettings {
+    fn default() -> Self {
+        Self {
+            default: PriceSource::default(),
+            assets: BTreeMap::new(),
+            workers: Self::default_workers(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+impl PriceSettings {
+    fn default_workers() -> usize {
+        4
+    }
+
+    fn default_timeout() -> u64 {
+        10
+    }
 }
 
 impl Config {
@@ -42,16 +148,27 @@ impl Config {
 
     pub fn default(config_path: &str) -> anyhow::Result<Config> {
         let default = Config {
-            encryption: util::random_pass(),
+            encryption: Some(util::random_pass()),
+            password: password::Settings::default(),
+            kdf: crypto::Strength::default(),
+            resolved: OnceCell::new(),
             files: Files {
                 ledger: Xdg::Config("ledger.csv".to_string()).filepath()?,
                 networth: Xdg::Config("networth.csv".to_string()).filepath()?,
+                recurring: None,
             },
-            exchange_key: "your app id from https://openexchangerates.org/signup".to_string(),
+            exchange_key: Some("your app id from https://openexchangerates.org/signup".to_string()),
+            exchange: ExchangeSettings::default(),
+            prices: PriceSettings::default(),
             currency: "EUR".to_string(),
             transfer: "Transfer".to_string(),
             ignored_accounts: vec!["Personal".to_string()],
             investments: "Investment".to_string(),
+            categories: vec![],
+            budgets: vec![],
+            import: import::Settings::default(),
+            export: export::Settings::default(),
+            backups: Backups::default(),
         };
 
         let mut file = File::create(config_path)?;
@@ -76,11 +193,122 @@ impl Config {
         shellexpand::tilde(&path).to_string()
     }
 
-    pub fn pass(&self) -> Option<String> {
-        self.encryption.to_owned()
+    /// Files of both modes that exist, each only once (they are the same with `LEDGER_PATH`).
+    pub fn filepaths(&self) -> Vec<(Mode, String)> {
+        let mut result: Vec<(Mode, String)> = Vec::new();
+
+        for mode in [Mode::Ledger, Mode::Networth].iter() {
+            let filepath = self.filepath(*mode);
+
+            if Path::new(&filepath).exists() && result.iter().all(|(_, v)| *v != filepath) {
+                result.push((*mode, filepath));
+            }
+        }
+
+        result
+    }
+
+    /// File with the definitions of recurring transactions (`recurring.yml` in the configuration
+    /// directory by default).
+    pub fn recurring_path(&self) -> anyhow::Result<String> {
+        match &self.files.recurring {
+            Some(path) => Ok(shellexpand::tilde(path).to_string()),
+            None => Xdg::Config("recurring.yml".to_string()).filepath(),
+        }
+    }
+
+    /// Directory where the copies of the files are kept.
+    pub fn backups_path(&self) -> anyhow::Result<String> {
+        match &self.backups.directory {
+            Some(path) => Ok(shellexpand::tilde(path).to_string()),
+            None => Xdg::Config("backups".to_string()).filepath(),
+        }
+    }
+
+    /// Password of the encrypted files, from `encryption` or else from the first source of
+    /// `password` that has it (none if the files are not encrypted).
+    pub fn pass(&self) -> anyhow::Result<Option<String>> {
+        if let Some(pass) = &self.encryption {
+            return Ok(Some(pass.to_owned()));
+        }
+
+        if let Some(pass) = self.resolved.get() {
+            return Ok(pass.to_owned());
+        }
+
+        let pass = self.password.resolve()?;
+
+        Ok(self.resolved.get_or_init(|| pass).to_owned())
+    }
+
+    /// Ask for the password twice when it comes from the prompt (rather than from the agent,
+    /// which might keep another one), before the files are encrypted with it for the first time.
+    pub fn confirm_pass(&self) -> anyhow::Result<()> {
+        if self.encryption.is_some() || self.resolved.get().is_some() || !self.password.prompted() {
+            return Ok(());
+        }
+
+        password::forget();
+
+        let pass = password::confirm("Password")?;
+
+        self.resolved.get_or_init(|| Some(pass));
+
+        Ok(())
+    }
+
+    /// Replace the password in the configuration file, keeping the rest of it as it is. Nothing
+    /// is written (and `false` is returned) when the password comes from another source, which
+    /// has to be updated instead.
+    pub fn set_pass(&mut self, pass: &str) -> anyhow::Result<bool> {
+        password::forget();
+
+        if self.encryption.is_none() && self.pass()?.is_some() {
+            return Ok(false);
+        }
+
+        let config_path = Config::path()?;
+
+        let content = std::fs::read_to_string(&config_path)?;
+
+        let value = format!("encryption: {}", serde_yaml::to_string(pass)?.trim_end());
+
+        let mut replaced = false;
+
+        let mut lines: Vec<String> = content
+            .lines()
+            .map(|line| {
+                if !replaced && line.starts_with("encryption:") {
+                    replaced = true;
+                    value.to_string()
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+
+        if !replaced {
+            lines.insert(0, value);
+        }
+
+        let directory = Path::new(&config_path)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        file.write_all(format!("{}\n", lines.join("\n")).as_bytes())?;
+        file.persist(&config_path).map_err(|e| e.error)?;
+
+        self.encryption = Some(pass.to_string());
+
+        Ok(true)
+    }
+
+    pub(crate) fn kdf(&self) -> crypto::Strength {
+        self.kdf
     }
 
-    pub fn exchange_key(&self) -> String {
+    pub fn exchange_key(&self) -> Option<String> {
         self.exchange_key.to_owned()
     }
 }
@@ -1,23 +1,227 @@
 use anyhow::{anyhow, Context};
-use sodiumoxide::crypto::pwhash;
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::pwhash::{argon2id13, scryptsalsa208sha256};
 use sodiumoxide::crypto::secretstream;
 
 use std::fs::File;
 use std::io::{Read, Write};
 
+use crate::Error;
+
 const CHUNK_SIZE: usize = 4096;
 const SIGNATURE: [u8; 4] = [0xC1, 0x0A, 0x4B, 0xED];
+// Files with a versioned header: signature, version, algorithm of the key derivation and its
+// limits, followed by the salt and the header of the stream.
+const SIGNATURE_VERSIONED: [u8; 4] = [0xC1, 0x0A, 0x4B, 0xEE];
+const VERSION: u8 = 2;
+// Version, algorithm and both limits (see `Kdf::read`).
+const KDF_HEADER_BYTES: usize = 18;
+
+/// Cost of deriving the key from the password of the files written (`kdf` in the
+/// configuration): the stronger, the longer (and the more memory) it takes to open the files.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strength {
+    #[default]
+    Interactive,
+    Moderate,
+    Sensitive,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Algorithm {
+    Scrypt = 1,
+    Argon2id = 2,
+}
+
+// Function used to derive the key of a file, as stored in its header.
+#[derive(Debug)]
+struct Kdf {
+    algorithm: Algorithm,
+    opslimit: u64,
+    memlimit: u64,
+}
+
+impl Kdf {
+    fn new(strength: Strength) -> Kdf {
+        let (opslimit, memlimit) = match strength {
+            Strength::Interactive => (
+                argon2id13::OPSLIMIT_INTERACTIVE.0,
+                argon2id13::MEMLIMIT_INTERACTIVE.0,
+            ),
+            Strength::Moderate => (
+                argon2id13::OPSLIMIT_MODERATE.0,
+                argon2id13::MEMLIMIT_MODERATE.0,
+            ),
+            Strength::Sensitive => (
+                argon2id13::OPSLIMIT_SENSITIVE.0,
+                argon2id13::MEMLIMIT_SENSITIVE.0,
+            ),
+        };
+
+        Kdf {
+            algorithm: Algorithm::Argon2id,
+            opslimit: opslimit as u64,
+            memlimit: memlimit as u64,
+        }
+    }
+
+    // Files written before the header was versioned.
+    fn legacy() -> Kdf {
+        Kdf {
+            algorithm: Algorithm::Scrypt,
+            opslimit: scryptsalsa208sha256::OPSLIMIT_INTERACTIVE.0 as u64,
+            memlimit: scryptsalsa208sha256::MEMLIMIT_INTERACTIVE.0 as u64,
+        }
+    }
+
+    fn read(in_file: &mut File) -> anyhow::Result<Kdf> {
+        let mut header = [0u8; KDF_HEADER_BYTES];
+        in_file.read_exact(&mut header)?;
+
+        if header[0] != VERSION {
+            return Err(anyhow!("Unsupported version of the file ({})", header[0]));
+        }
+
+        let algorithm = match header[1] {
+            1 => Algorithm::Scrypt,
+            2 => Algorithm::Argon2id,
+            value => return Err(anyhow!("Unknown key derivation algorithm ({})", value)),
+        };
+
+        let mut limit = [0u8; 8];
+
+        limit.copy_from_slice(&header[2..10]);
+        let opslimit = u64::from_le_bytes(limit);
+
+        limit.copy_from_slice(&header[10..18]);
+        let memlimit = u64::from_le_bytes(limit);
+
+        // Never written by this program, and the key would take too long (or too much memory)
+        // to derive with them.
+        let (max_opslimit, max_memlimit) = match algorithm {
+            Algorithm::Scrypt => (
+                scryptsalsa208sha256::OPSLIMIT_SENSITIVE.0,
+                scryptsalsa208sha256::MEMLIMIT_SENSITIVE.0,
+            ),
+            Algorithm::Argon2id => (
+                argon2id13::OPSLIMIT_SENSITIVE.0,
+                argon2id13::MEMLIMIT_SENSITIVE.0,
+            ),
+        };
+
+        if opslimit > max_opslimit as u64 || memlimit > max_memlimit as u64 {
+            return Err(Error::KdfLimits { opslimit, memlimit }.into());
+        }
+
+        Ok(Kdf {
+            algorithm,
+            opslimit,
+            memlimit,
+        })
+    }
+
+    fn write(&self, out_file: &mut File) -> anyhow::Result<()> {
+        out_file.write_all(&SIGNATURE_VERSIONED)?;
+        out_file.write_all(&[VERSION, self.algorithm as u8])?;
+        out_file.write_all(&self.opslimit.to_le_bytes())?;
+        out_file.write_all(&self.memlimit.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    fn salt_bytes(&self) -> usize {
+        match self.algorithm {
+            Algorithm::Scrypt => scryptsalsa208sha256::SALTBYTES,
+            Algorithm::Argon2id => argon2id13::SALTBYTES,
+        }
+    }
+
+    fn gen_salt(&self) -> Vec<u8> {
+        match self.algorithm {
+            Algorithm::Scrypt => scryptsalsa208sha256::gen_salt().0.to_vec(),
+            Algorithm::Argon2id => argon2id13::gen_salt().0.to_vec(),
+        }
+    }
+
+    fn key(&self, password: &str, salt: &[u8]) -> anyhow::Result<secretstream::Key> {
+        let mut key = [0u8; secretstream::KEYBYTES];
+
+        let opslimit = self.opslimit as usize;
+        let memlimit = self.memlimit as usize;
+
+        let result = match self.algorithm {
+            Algorithm::Scrypt => scryptsalsa208sha256::derive_key(
+                &mut key,
+                password.as_bytes(),
+                &scryptsalsa208sha256::Salt::from_slice(salt).context("Invalid salt")?,
+                scryptsalsa208sha256::OpsLimit(opslimit),
+                scryptsalsa208sha256::MemLimit(memlimit),
+            )
+            .map(|_| ()),
+            Algorithm::Argon2id => argon2id13::derive_key(
+                &mut key,
+                password.as_bytes(),
+                &argon2id13::Salt::from_slice(salt).context("Invalid salt")?,
+                argon2id13::OpsLimit(opslimit),
+                argon2id13::MemLimit(memlimit),
+            )
+            .map(|_| ()),
+        };
+
+        match result {
+            Ok(_) => Ok(secretstream::Key(key)),
+            Err(_) => Err(anyhow!("Deriving key failed")),
+        }
+    }
+}
 
-pub fn encrypt(in_file: &mut File, out_file: &mut File, password: &str) -> anyhow::Result<()> {
+/// How the content of a file is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Plaintext,
+    /// Encrypted, starting with the signature.
+    Encrypted,
+    /// Encrypted before the signature was added to the files (starting with the salt).
+    Unsigned,
+}
+
+/// Find out how the file is stored: with the signature it is encrypted, while otherwise it is
+/// plain text as long as it is valid UTF-8 (which random bytes are not).
+pub fn kind(path: &str) -> anyhow::Result<Kind> {
+    let mut file = File::open(path).with_context(|| format!("Could not open '{}'", path))?;
+
+    let mut buf = Vec::with_capacity(CHUNK_SIZE);
+    (&mut file).take(CHUNK_SIZE as u64).read_to_end(&mut buf)?;
+
+    if buf.starts_with(&SIGNATURE) || buf.starts_with(&SIGNATURE_VERSIONED) {
+        return Ok(Kind::Encrypted);
+    }
+
+    match std::str::from_utf8(&buf) {
+        Ok(_) => Ok(Kind::Plaintext),
+        // A character cut at the end of what was read.
+        Err(e) if e.error_len().is_none() => Ok(Kind::Plaintext),
+        Err(_) => Ok(Kind::Unsigned),
+    }
+}
+
+pub fn encrypt(
+    in_file: &mut File,
+    out_file: &mut File,
+    password: &str,
+    strength: Strength,
+) -> anyhow::Result<()> {
     let mut buf = [0; CHUNK_SIZE];
     let mut bytes_left = in_file.metadata()?.len();
 
-    out_file.write_all(&SIGNATURE)?;
+    let kdf = Kdf::new(strength);
+    kdf.write(out_file)?;
 
-    let salt = pwhash::gen_salt();
-    out_file.write_all(&salt.0)?;
+    let salt = kdf.gen_salt();
+    out_file.write_all(&salt)?;
 
-    let key = key(password, &salt)?;
+    let key = kdf.key(password, &salt)?;
     let (mut stream, header) = secretstream::Stream::init_push(&key)
         .ok()
         .context("init_push failed")?;
@@ -47,31 +251,56 @@ pub fn encrypt(in_file: &mut File, out_file: &mut File, password: &str) -> anyho
 }
 
 pub fn decrypt(in_file: &mut File, out_file: &mut File, password: &str) -> anyhow::Result<()> {
-    if in_file.metadata()?.len()
-        <= (pwhash::SALTBYTES + secretstream::HEADERBYTES + SIGNATURE.len()) as u64
-    {
-        return Err(anyhow!("File not big enough to have been encrypted"));
+    let length = in_file.metadata()?.len() as usize;
+
+    let too_small = || anyhow!("File not big enough to have been encrypted");
+
+    if length < SIGNATURE.len() {
+        return Err(too_small());
     }
 
-    let mut salt = [0u8; pwhash::SALTBYTES];
     let mut signature = [0u8; 4];
-
     in_file.read_exact(&mut signature)?;
-    if signature == SIGNATURE {
-        // if the signature is present, read into all of salt
+
+    let versioned = signature == SIGNATURE_VERSIONED;
+
+    if versioned && length < SIGNATURE.len() + KDF_HEADER_BYTES {
+        return Err(too_small());
+    }
+
+    let kdf = if versioned {
+        Kdf::read(in_file)?
+    } else {
+        Kdf::legacy()
+    };
+
+    // What comes before the content: the signature (missing in the oldest files), the header of
+    // the key derivation (only in versioned files), the salt and the header of the stream.
+    let signed = match (versioned, signature == SIGNATURE) {
+        (true, _) => SIGNATURE.len() + KDF_HEADER_BYTES,
+        (false, true) => SIGNATURE.len(),
+        (false, false) => 0,
+    };
+
+    if length <= signed + kdf.salt_bytes() + secretstream::HEADERBYTES {
+        return Err(too_small());
+    }
+
+    let mut salt = vec![0u8; kdf.salt_bytes()];
+
+    if signed > 0 {
         in_file.read_exact(&mut salt)?;
     } else {
-        // or take the bytes from signature and read the rest from file
+        // Without a signature, what was read is the start of the salt.
         salt[..4].copy_from_slice(&signature);
         in_file.read_exact(&mut salt[4..])?;
     }
-    let salt = pwhash::Salt(salt);
 
     let mut header = [0u8; secretstream::HEADERBYTES];
     in_file.read_exact(&mut header)?;
     let header = secretstream::Header(header);
 
-    let key = key(password, &salt)?;
+    let key = kdf.key(password, &salt)?;
 
     let mut buffer = [0u8; CHUNK_SIZE + secretstream::ABYTES];
     let mut stream = secretstream::Stream::init_pull(&header, &key)
@@ -94,17 +323,149 @@ pub fn decrypt(in_file: &mut File, out_file: &mut File, password: &str) -> anyho
     Ok(())
 }
 
-fn key(password: &str, salt: &pwhash::Salt) -> anyhow::Result<secretstream::Key> {
-    let mut key = [0u8; secretstream::KEYBYTES];
-
-    match pwhash::derive_key(
-        &mut key,
-        password.as_bytes(),
-        salt,
-        pwhash::OPSLIMIT_INTERACTIVE,
-        pwhash::MEMLIMIT_INTERACTIVE,
-    ) {
-        Ok(_) => Ok(secretstream::Key(key)),
-        Err(_) => Err(anyhow!("Deriving key failed")),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Seek, SeekFrom};
+
+    const CONTENT: &[u8] = b"Account,Date,Category,Amount\nBank,2024-01-31,Food,-12.50\n";
+    const PASSWORD: &str = "correct horse";
+
+    fn file(bytes: &[u8]) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(bytes).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    fn content(file: &mut File) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn encrypted(strength: Strength) -> Vec<u8> {
+        let mut output = tempfile::tempfile().unwrap();
+        encrypt(&mut file(CONTENT), &mut output, PASSWORD, strength).unwrap();
+        content(&mut output)
+    }
+
+    fn decrypted(bytes: &[u8], password: &str) -> anyhow::Result<Vec<u8>> {
+        let mut output = tempfile::tempfile()?;
+        decrypt(&mut file(bytes), &mut output, password)?;
+        Ok(content(&mut output))
+    }
+
+    fn kind_of(bytes: &[u8]) -> Kind {
+        let path = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(path.path(), bytes).unwrap();
+        kind(&path.path().to_string_lossy()).unwrap()
+    }
+
+    // As written before the header was versioned: the salt of scrypt (after the signature, once
+    // there was one) and the header of the stream.
+    fn legacy(signed: bool) -> Vec<u8> {
+        let kdf = Kdf::legacy();
+        let salt = kdf.gen_salt();
+        let key = kdf.key(PASSWORD, &salt).unwrap();
+        let (mut stream, header) = secretstream::Stream::init_push(&key).unwrap();
+
+        let mut bytes = Vec::new();
+
+        if signed {
+            bytes.extend_from_slice(&SIGNATURE);
+        }
+
+        bytes.extend_from_slice(&salt);
+        bytes.extend_from_slice(&header.0);
+        bytes.extend(
+            stream
+                .push(CONTENT, None, secretstream::Tag::Final)
+                .unwrap(),
+        );
+
+        bytes
+    }
+
+    fn round_trip(strength: Strength) {
+        let bytes = encrypted(strength);
+
+        assert_eq!(bytes[..4], SIGNATURE_VERSIONED);
+        assert_eq!(bytes[4], VERSION);
+        assert_eq!(bytes[5], Algorithm::Argon2id as u8);
+
+        let kdf = Kdf::read(&mut file(&bytes[4..])).unwrap();
+        let expected = Kdf::new(strength);
+
+        assert_eq!(kdf.opslimit, expected.opslimit);
+        assert_eq!(kdf.memlimit, expected.memlimit);
+
+        assert_eq!(decrypted(&bytes, PASSWORD).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn decrypts_legacy_unsigned_files() {
+        assert_eq!(decrypted(&legacy(false), PASSWORD).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn decrypts_legacy_signed_files() {
+        let bytes = legacy(true);
+
+        assert_eq!(kind_of(&bytes), Kind::Encrypted);
+        assert_eq!(decrypted(&bytes, PASSWORD).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn round_trips_interactive() {
+        round_trip(Strength::Interactive);
+    }
+
+    #[test]
+    fn rejects_wrong_passwords() {
+        assert!(decrypted(&encrypted(Strength::Interactive), "wrong").is_err());
+    }
+
+    #[test]
+    fn round_trips_moderate() {
+        round_trip(Strength::Moderate);
+    }
+
+    #[test]
+    fn round_trips_sensitive() {
+        round_trip(Strength::Sensitive);
+    }
+
+    #[test]
+    fn rejects_limits_above_the_strongest() {
+        let bytes = encrypted(Strength::Interactive);
+
+        // Opslimit, then memlimit, after the signature, the version and the algorithm.
+        for offset in [6, 14].iter() {
+            let mut bytes = bytes.clone();
+            bytes[*offset..*offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+            let error = decrypted(&bytes, PASSWORD).unwrap_err();
+
+            assert!(matches!(
+                error.downcast_ref::<Error>(),
+                Some(Error::KdfLimits { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_files_shorter_than_their_header() {
+        let mut bytes = encrypted(Strength::Interactive);
+
+        bytes.truncate(
+            SIGNATURE.len() + KDF_HEADER_BYTES + argon2id13::SALTBYTES + secretstream::HEADERBYTES,
+        );
+
+        let error = decrypted(&bytes, PASSWORD).unwrap_err();
+
+        assert!(error.to_string().contains("not big enough"));
     }
 }
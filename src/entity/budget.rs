@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::money::{Currency, Money};
+use crate::exchange::Exchange;
+use crate::util;
+
+/// How often the amount of a budget is available.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    #[default]
+    Monthly,
+    Yearly,
+}
+
+/// Amount planned to be spent on a category (`budgets` in the configuration), in the default
+/// currency unless stated otherwise.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Budget {
+    pub category: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub period: Period,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// The budgets configured, for a given period and in a single currency.
+#[derive(Clone, Debug, Default)]
+pub struct Budgets {
+    values: Vec<(String, Money)>,
+}
+
+impl Budgets {
+    /// Budgets are scaled by the number of months of the period (e.g. a monthly budget is
+    /// doubled for a period of two months), so there are none for a period without bounds.
+    pub fn new(
+        config: &Config,
+        currency: Currency,
+        exchange: &Exchange,
+        start: Option<Date>,
+        end: Option<Date>,
+    ) -> anyhow::Result<Budgets> {
+        let months = match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                (end.year() - start.year()) * 12 + (end.month() as i32 - start.month() as i32) + 1
+            }
+            _ => return Ok(Self::default()),
+        };
+
+        let mut values = Vec::new();
+
+        for budget in config.budgets.iter() {
+            let original = util::currency(budget.currency.as_ref(), config)?;
+
+            let amount = Money::parse(&budget.amount.abs().to_string(), original)?
+                .exchange(currency, exchange, Date::today())?;
+
+            let scaled = match budget.period {
+                Period::Monthly => amount.cents() * months as i64,
+                Period::Yearly => amount.cents() * months as i64 / 12,
+            };
+
+            values.push((budget.category.to_string(), Money::new(currency, scaled)));
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Categories with a budget (in the order they are configured) and the amount planned.
+    pub fn values(&self) -> &[(String, Money)] {
+        &self.values
+    }
+
+    pub fn get(&self, category: &str) -> Option<Money> {
+        self.values
+            .iter()
+            .find(|(name, _)| name.to_uppercase() == category.to_uppercase())
+            .map(|(_, amount)| *amount)
+    }
+}
+
+/// What is left of the budget after the amount spent (negative when overspent).
+pub fn remaining(budget: Money, actual: Money) -> crate::Result<Money> {
+    budget.checked_sub(actual.abs())
+}
@@ -89,6 +89,10 @@ impl Date {
         self.value.month()
     }
 
+    pub fn day(self) -> u32 {
+        self.value.day()
+    }
+
     pub fn since(self, rhs: Date) -> chrono::Duration {
         self.value.signed_duration_since(rhs.value)
     }
@@ -120,7 +124,7 @@ impl Date {
                     Ok(datetime) => Ok(datetime.naive_local().date().into()),
                     Err(_) => Err(anyhow!(
                         "Invalid format for date: {} (only accept %Y-%m-%d)",
-                        value.to_string()
+                        value
                     )),
                 },
             },
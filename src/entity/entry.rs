@@ -83,10 +83,16 @@ impl Liner for Entry {
         self.exported.to_string()
     }
 
+    fn transfer(&self) -> String {
+        String::new()
+    }
+
     fn set_exported(&mut self, value: String) {
         self.exported = value;
     }
 
+    fn set_transfer(&mut self, _value: String) {}
+
     fn set_invested(&mut self, value: Money) {
         self.invested = value;
     }
@@ -98,9 +104,9 @@ impl Liner for Entry {
     fn exchange(&self, to: Currency, exchange: &Exchange) -> anyhow::Result<Line> {
         Ok(Entry {
             date: self.date,
-            invested: self.invested.exchange(to, exchange)?,
-            investment: self.investment.exchange(to, exchange)?,
-            amount: self.amount.exchange(to, exchange)?,
+            invested: self.invested.exchange(to, exchange, self.date)?,
+            investment: self.investment.exchange(to, exchange, self.date)?,
+            amount: self.amount.exchange(to, exchange, self.date)?,
             currency: to,
             exported: self.exported.to_string(),
         }
@@ -56,9 +56,12 @@ pub trait Liner {
     fn trip(&self) -> String;
     fn investment(&self) -> Money;
     fn exported(&self) -> String;
+    fn transfer(&self) -> String;
     fn set_exported(&mut self, value: String);
+    fn set_transfer(&mut self, value: String);
     fn set_invested(&mut self, value: Money);
     fn set_amount(&mut self, value: Money);
+    /// Convert the line to another currency, at the rates of the date of the line.
     fn exchange(&self, to: Currency, exchange: &Exchange) -> anyhow::Result<Line>;
     fn write(&self, wrt: &mut csv::Writer<File>) -> anyhow::Result<()>;
 }
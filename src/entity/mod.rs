@@ -1,8 +1,11 @@
+pub mod budget;
 pub mod date;
 pub mod entry;
 pub mod line;
 pub mod money;
 pub mod networth;
+pub mod recurring;
 pub mod report;
 pub mod total;
 pub mod transaction;
+pub mod transfer;
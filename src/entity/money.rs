@@ -2,12 +2,12 @@ use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 
 use std::cmp::Ordering;
-use std::ops::{Add, AddAssign, Mul, Sub};
+use std::ops::Mul;
 
+use crate::entity::date::Date;
+use crate::error::Error;
 use crate::exchange::Exchange;
 
-static DIFFERENT_CURRENCIES: &str = "Cannot perform operations between different currencies";
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Currency {
     value: iso_currency::Currency,
@@ -64,10 +64,7 @@ impl Currency {
     pub fn parse(code: &str) -> anyhow::Result<Currency> {
         match iso_currency::Currency::from_code(code) {
             Some(value) => Ok(value.into()),
-            None => Err(anyhow!(
-                "The currency code '{}' does not exist",
-                code.to_string()
-            )),
+            None => Err(anyhow!("The currency code '{}' does not exist", code)),
         }
     }
 
@@ -114,49 +111,6 @@ impl std::fmt::Display for Money {
     }
 }
 
-impl Add for Money {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
-        if self.currency != other.currency {
-            crate::werr!(2, "{} + {}: {}", self, other, DIFFERENT_CURRENCIES)
-        };
-
-        Self {
-            value: self.value + other.value,
-            currency: self.currency,
-        }
-    }
-}
-
-impl AddAssign for Money {
-    fn add_assign(&mut self, other: Self) {
-        if self.currency != other.currency {
-            crate::werr!(2, "{} += {}: {}", self, other, DIFFERENT_CURRENCIES)
-        };
-
-        *self = Self {
-            value: self.value + other.value,
-            currency: self.currency,
-        }
-    }
-}
-
-impl Sub for Money {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        if self.currency != other.currency {
-            crate::werr!(2, "{} - {}: {}", self, other, DIFFERENT_CURRENCIES)
-        };
-
-        Self {
-            value: self.value - other.value,
-            currency: self.currency,
-        }
-    }
-}
-
 impl Mul<i64> for Money {
     type Output = Self;
 
@@ -237,12 +191,13 @@ impl Money {
         self.value
     }
 
-    pub fn exchange(&self, to: Currency, exchange: &Exchange) -> anyhow::Result<Money> {
+    /// Convert the amount to another currency, using the rates of the date provided.
+    pub fn exchange(&self, to: Currency, exchange: &Exchange, date: Date) -> anyhow::Result<Money> {
         if self.currency == to {
             return Ok(*self);
         }
 
-        let rate = exchange.rate(self.currency(), to)? as f64;
+        let rate = exchange.rate(self.currency(), to, date)? as f64;
 
         let dec_adjust =
             10f64.powi(to.decimal_places() as i32 - self.currency.decimal_places() as i32);
@@ -254,6 +209,50 @@ impl Money {
         })
     }
 
+    /// Sum of both amounts, which must be in the same currency.
+    pub fn checked_add(self, other: Money) -> crate::Result<Money> {
+        self.check(other, "+")?;
+
+        let value = self.value.checked_add(other.value).ok_or(Error::Overflow {
+            operation: "+",
+            left: self,
+            right: other,
+        })?;
+
+        Ok(Self {
+            value,
+            currency: self.currency,
+        })
+    }
+
+    /// Difference between both amounts, which must be in the same currency.
+    pub fn checked_sub(self, other: Money) -> crate::Result<Money> {
+        self.check(other, "-")?;
+
+        let value = self.value.checked_sub(other.value).ok_or(Error::Overflow {
+            operation: "-",
+            left: self,
+            right: other,
+        })?;
+
+        Ok(Self {
+            value,
+            currency: self.currency,
+        })
+    }
+
+    fn check(self, other: Money, operation: &'static str) -> crate::Result<()> {
+        if self.currency != other.currency {
+            return Err(Error::CurrencyMismatch {
+                operation,
+                left: self,
+                right: other,
+            });
+        };
+
+        Ok(())
+    }
+
     pub fn abs(&self) -> Money {
         Money {
             value: self.value.abs(),
@@ -1,15 +1,16 @@
+use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, HashMap};
-use std::ops::AddAssign;
 
 use crate::config::Config;
 use crate::entity::date::Date;
-use crate::entity::entry::Entry;
+use crate::entity::entry;
 use crate::entity::line::{Line, Liner};
 use crate::entity::money::{Currency, Money};
+use crate::error::Error;
 use crate::exchange::Exchange;
 use crate::filter::Filter;
+use crate::price::{Asset, Prices};
 use crate::resource::Resource;
-use crate::service::justetf::Asset;
 use crate::Mode;
 
 #[derive(Debug)]
@@ -25,6 +26,7 @@ impl Networth {
     pub fn new(
         config: &Config,
         exchange: &Exchange,
+        prices: &mut Prices,
         currency: Currency,
     ) -> anyhow::Result<Networth> {
         let mut networth = Self {
@@ -47,11 +49,22 @@ impl Networth {
             Ok(())
         })?;
 
+        networth.quote(prices, exchange)?;
+
         Ok(networth)
     }
 
-    pub fn total(&self) -> Money {
-        self.current_on(Date::today()) + self.investments()
+    /// Investments whose price could not be obtained (and are, therefore, not valued).
+    pub fn unavailable(&self) -> Vec<&Investment> {
+        self.investments
+            .values()
+            .filter(|investment| investment.error.is_some())
+            .collect()
+    }
+
+    pub fn total(&self) -> crate::Result<Money> {
+        self.current_on(Date::today())
+            .checked_add(self.investments()?)
     }
 
     pub fn invested_on(&self, date: Date) -> Money {
@@ -76,55 +89,93 @@ impl Networth {
             .to_owned()
     }
 
-    pub fn current(&self) -> Line {
+    pub fn current(&self) -> crate::Result<Line> {
         let today = Date::today();
-        let investment = self.investments();
+        let investment = self.investments()?;
 
-        Entry {
+        Ok(entry::Entry {
             date: today,
             invested: self.invested_on(today),
-            amount: self.current_on(today) + self.investments(),
+            amount: self.current_on(today).checked_add(investment)?,
             currency: self.currency,
             investment,
             exported: String::new(),
         }
-        .into()
+        .into())
     }
 
     fn add(&mut self, record: &Line, filter: &Filter, exchange: &Exchange) -> anyhow::Result<()> {
         let exchanged = record.exchange(self.currency, exchange)?;
 
-        self.cash += exchanged.amount();
+        self.cash = self.cash.checked_add(exchanged.amount())?;
 
-        let cash = self.cash;
-
-        self.current
-            .entry(exchanged.date())
-            .and_modify(|i| *i = cash)
-            .or_insert_with(|| cash);
+        self.current.insert(exchanged.date(), self.cash);
 
         if filter.investment(&exchanged.category()) {
-            let currency = self.currency;
+            match self.investments.entry(exchanged.description()) {
+                Entry::Occupied(mut investment) => investment.get_mut().add(&exchanged)?,
+                Entry::Vacant(investment) => {
+                    investment.insert(Investment::new(&exchanged, self.currency)?);
+                }
+            };
+
+            let invested = self
+                .invested_on(exchanged.date())
+                .checked_sub(exchanged.amount())?;
+
+            self.invested.insert(exchanged.date(), invested);
+        }
+
+        Ok(())
+    }
+
+    // Prices are only requested once all the transactions are known, so that they can be fetched
+    // at the same time and only for the investments still held.
+    fn quote(&mut self, prices: &mut Prices, exchange: &Exchange) -> anyhow::Result<()> {
+        let isins: Vec<String> = self
+            .investments
+            .values()
+            .filter(|investment| investment.quantity != 0)
+            .map(|investment| investment.code.to_string())
+            .collect();
+
+        let mut assets = prices.assets(&isins, self.currency, exchange)?;
 
-            self.investments
-                .entry(exchanged.description())
-                .and_modify(|i| *i += exchanged.clone())
-                .or_insert_with(|| Investment::new(&exchanged, currency));
+        for isin in isins {
+            let investment = match self.investments.get_mut(&isin) {
+                Some(value) => value,
+                None => continue,
+            };
 
-            self.invested
-                .entry(exchanged.date())
-                .and_modify(|i| *i += exchanged.amount() * -1)
-                .or_insert_with(|| exchanged.amount() * -1);
+            match assets.remove(&isin) {
+                Some(Ok(asset)) => {
+                    investment.price = asset.quote;
+                    investment.asset = Some(asset);
+                }
+                Some(Err(e)) => {
+                    log::warn!("Could not obtain the price of '{}': {}", isin, e);
+                    investment.error = Some(Error::Asset {
+                        isin: isin.to_string(),
+                        reason: e.to_string(),
+                    });
+                }
+                None => {
+                    investment.error = Some(Error::Asset {
+                        isin: isin.to_string(),
+                        reason: "No price was obtained".to_string(),
+                    })
+                }
+            }
         }
 
         Ok(())
     }
 
-    fn investments(&self) -> Money {
+    fn investments(&self) -> crate::Result<Money> {
         self.investments
             .values()
-            .fold(Money::new(self.currency, 0), |acc, investment| {
-                acc + investment.value()
+            .try_fold(Money::new(self.currency, 0), |acc, investment| {
+                acc.checked_add(investment.value())
             })
     }
 }
@@ -135,28 +186,33 @@ pub struct Investment {
     pub spent: Money,
     pub quantity: i64,
     pub currency: Currency,
-    pub asset: Asset,
+    pub asset: Option<Asset>,
     pub price: Money,
+    /// Why the price could not be obtained, if that was the case.
+    pub error: Option<Error>,
 }
 
 impl Investment {
-    pub fn new(record: &Line, currency: Currency) -> Self {
-        let asset = Asset::download(&record.description(), &currency)
-            .unwrap_or_else(|e| crate::werr!(1, "{}", e));
+    pub fn new(record: &Line, currency: Currency) -> crate::Result<Self> {
+        let quantity = Investment::quantity(record)?;
 
-        let quantity = record
-            .quantity()
-            .parse::<i64>()
-            .unwrap_or_else(|e| crate::werr!(1, "{}", e));
-
-        Self {
+        Ok(Self {
             code: record.description(),
             spent: record.amount(),
-            price: asset.quote,
+            price: Money::new(currency, 0),
             currency,
             quantity,
-            asset,
-        }
+            asset: None,
+            error: None,
+        })
+    }
+
+    /// Include another transaction of the same investment (e.g. buying more shares).
+    pub fn add(&mut self, other: &Line) -> crate::Result<()> {
+        self.spent = self.spent.checked_add(other.amount())?;
+        self.quantity += Investment::quantity(other)?;
+
+        Ok(())
     }
 
     pub fn value(&self) -> Money {
@@ -164,24 +220,24 @@ impl Investment {
     }
 
     pub fn name(&self) -> String {
-        self.asset.name.to_string()
+        match &self.asset {
+            Some(asset) => asset.name.to_string(),
+            None => self.code.to_string(),
+        }
+    }
+
+    /// Whether the price is the last one known, since the provider failed.
+    pub fn stale(&self) -> bool {
+        self.asset.as_ref().is_some_and(|asset| asset.stale)
     }
-}
 
-impl AddAssign<Line> for Investment {
-    fn add_assign(&mut self, other: Line) {
-        let quantity = other
+    fn quantity(record: &Line) -> crate::Result<i64> {
+        record
             .quantity()
             .parse::<i64>()
-            .unwrap_or_else(|e| crate::werr!(1, "{}", e));
-
-        *self = Self {
-            code: self.code.to_string(),
-            spent: self.spent + other.amount(),
-            quantity: self.quantity + quantity,
-            currency: self.currency,
-            asset: self.asset.clone(),
-            price: self.price,
-        }
+            .map_err(|_| Error::InvalidQuantity {
+                code: record.description(),
+                value: record.quantity(),
+            })
     }
 }
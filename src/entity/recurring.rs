@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::entity::date::Date;
+use crate::entity::line::Line;
+use crate::entity::money::{Currency, Money};
+use crate::entity::transaction::Transaction;
+use crate::xdg::Xdg;
+
+const RECURRING_STATE_FILENAME: &str = "recurring.yml";
+
+/// When a recurring transaction occurs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Schedule {
+    /// Every month, on the given day (or on the last day, for shorter months).
+    Monthly { day: u32 },
+    /// Every N weeks, on the same day of the week as the start.
+    Weekly {
+        #[serde(default = "Schedule::default_every")]
+        every: u32,
+    },
+}
+
+impl Schedule {
+    fn default_every() -> u32 {
+        1
+    }
+
+    fn valid(&self) -> bool {
+        match self {
+            Schedule::Monthly { day } => (1..=31).contains(day),
+            Schedule::Weekly { every } => *every > 0,
+        }
+    }
+}
+
+/// A transaction booked periodically (e.g. rent, salaries or subscriptions).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recurring {
+    /// Identifies the definition, to know when it was last booked.
+    pub name: String,
+    pub account: String,
+    pub category: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub venue: String,
+    pub amount: f64,
+    pub currency: Currency,
+    pub schedule: Schedule,
+    pub start: Date,
+    #[serde(default)]
+    pub end: Option<Date>,
+}
+
+impl Recurring {
+    /// Load the definitions from a YAML file (a list of definitions).
+    pub fn load(path: &str) -> anyhow::Result<Vec<Recurring>> {
+        if !Path::new(path).exists() {
+            return Ok(vec![]);
+        }
+
+        let file = File::open(path).with_context(|| format!("Could not open '{}'", path))?;
+
+        let definitions: Vec<Recurring> = serde_yaml::from_reader(file)?;
+
+        if let Some(definition) = definitions.iter().find(|v| !v.schedule.valid()) {
+            return Err(anyhow!("The schedule of '{}' is invalid", definition.name));
+        }
+
+        Ok(definitions)
+    }
+
+    /// Dates when the transaction occurs after `after` (or since the start) and until `until`.
+    pub fn occurrences(&self, after: Option<Date>, until: Date) -> Vec<Date> {
+        let until = match self.end {
+            Some(end) if end < until => end,
+            _ => until,
+        };
+
+        let mut result = Vec::new();
+
+        let mut index = 0;
+
+        while let Some(date) = self.nth(index) {
+            if date > until {
+                break;
+            }
+
+            if date >= self.start && after.is_none_or(|value| date > value) {
+                result.push(date);
+            }
+
+            index += 1;
+        }
+
+        result
+    }
+
+    pub fn line(&self, date: Date) -> anyhow::Result<Line> {
+        Ok(Transaction {
+            account: self.account.to_string(),
+            date,
+            category: self.category.to_string(),
+            description: self.description.to_string(),
+            venue: self.venue.to_string(),
+            amount: Money::parse(&self.amount.to_string(), self.currency)?,
+            currency: self.currency,
+            ..Default::default()
+        }
+        .into())
+    }
+
+    fn nth(&self, index: u32) -> Option<Date> {
+        let start: NaiveDate = self.start.into();
+
+        let date = match self.schedule {
+            Schedule::Monthly { day } => {
+                let months = start.month0() + index;
+                let year = start.year() + (months / 12) as i32;
+                let first = NaiveDate::from_ymd_opt(year, months % 12 + 1, 1)?;
+
+                first.with_day(day.min(Date::from(first).end_of_month().day()))?
+            }
+            Schedule::Weekly { every } => {
+                start.checked_add_signed(Duration::weeks((every * index) as i64))?
+            }
+        };
+
+        Some(date.into())
+    }
+}
+
+/// The date of the last occurrence booked for each definition, kept in the cache directory.
+#[derive(Debug)]
+pub struct State {
+    filepath: String,
+    pub booked: BTreeMap<String, Date>,
+}
+
+impl State {
+    pub fn load() -> anyhow::Result<State> {
+        let filepath = Xdg::Cache(RECURRING_STATE_FILENAME.to_string()).filepath()?;
+
+        let booked = if Path::new(&filepath).exists() {
+            serde_yaml::from_reader(File::open(&filepath)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { filepath, booked })
+    }
+
+    // Written next to the state and moved over it, so that an interrupted write does not lose
+    // the occurrences already booked.
+    pub fn store(&self) -> anyhow::Result<()> {
+        let directory = Path::new(&self.filepath)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        let yaml = serde_yaml::to_string(&self.booked)?;
+        file.write_all(yaml.as_bytes())?;
+        file.persist(&self.filepath).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
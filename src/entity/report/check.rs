@@ -1,19 +1,21 @@
 use prettytable::{format, Cell, Row, Table};
+use serde::Serialize;
 
 use std::cmp::Ordering;
-use std::ops::AddAssign;
 
 use crate::config::Config;
 use crate::entity::date::Date;
 use crate::entity::line::{Line, Liner};
 use crate::entity::money::Money;
 use crate::filter::Filter;
+use crate::output::{Render, Section};
 use crate::resource::Resource;
 use crate::Mode;
 
-#[derive(Default)]
+#[derive(Default, Serialize)]
 pub struct Report {
     items: Vec<Item>,
+    #[serde(skip)]
     current: Option<Item>,
 }
 
@@ -47,7 +49,7 @@ impl Report {
                 None => item,
                 Some(mut existing_item) => {
                     if existing_item == item {
-                        existing_item += item;
+                        existing_item.add(item)?;
                         existing_item
                     } else {
                         report.items.push(existing_item);
@@ -87,7 +89,7 @@ impl Report {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize)]
 struct Item {
     account: String,
     date: Date,
@@ -137,13 +139,13 @@ impl PartialOrd for Item {
     }
 }
 
-impl AddAssign for Item {
-    fn add_assign(&mut self, other: Self) {
-        self.amount += other.amount;
+impl Item {
+    fn add(&mut self, other: Item) -> crate::Result<()> {
+        self.amount = self.amount.checked_add(other.amount)?;
+
+        Ok(())
     }
-}
 
-impl Item {
     fn row(&self) -> Row {
         Row::new(vec![
             Cell::new(&self.account).style_spec("bFW"),
@@ -153,3 +155,26 @@ impl Item {
         ])
     }
 }
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Report", &["Account", "Date", "Identifier", "Amount"]);
+
+        for item in self.items.iter() {
+            section.rows.push(vec![
+                item.account.to_string(),
+                item.date.to_string(),
+                item.identifier.to_string(),
+                item.amount.to_storage(),
+            ]);
+        }
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use clap::ValueEnum;
+use prettytable::{format, Cell, Row, Table};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::money::{Currency, Money};
+use crate::entity::report::general;
+use crate::exchange::Exchange;
+use crate::filter::Filter;
+use crate::output::{Render, Section};
+use crate::util;
+
+/// Length of each period compared.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Granularity {
+    Month,
+    Year,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Period {
+    pub label: String,
+    pub start: Date,
+    pub end: Date,
+}
+
+impl Granularity {
+    /// The last `count` periods, up to the one including `last` (the oldest first).
+    pub fn periods(self, last: Date, count: u32) -> Vec<Period> {
+        let mut periods: Vec<Period> = (0..count.max(1) as i32)
+            .map(|offset| match self {
+                Granularity::Month => {
+                    let months = last.year() * 12 + last.month() as i32 - 1 - offset;
+                    let start =
+                        Date::from_ymd(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1);
+
+                    Period {
+                        label: start.format("%Y-%m").to_string(),
+                        start,
+                        end: start.end_of_month(),
+                    }
+                }
+                Granularity::Year => {
+                    let year = last.year() - offset;
+
+                    Period {
+                        label: year.to_string(),
+                        start: Date::from_ymd(year, 1, 1),
+                        end: Date::from_ymd(year, 12, 31),
+                    }
+                }
+            })
+            .collect();
+
+        periods.reverse();
+
+        periods
+    }
+}
+
+/// Amount per category (rows) and period (columns), with the difference to the previous period.
+#[derive(Debug)]
+pub struct Report {
+    currency: Currency,
+    periods: Vec<Period>,
+    reports: Vec<general::Report>,
+    categories: Vec<String>,
+}
+
+impl Report {
+    /// Each period is processed as a `general::Report` (with the same handling of transfers and
+    /// excluded categories), using the filter with the same index.
+    pub fn new(
+        currency: Currency,
+        config: &Config,
+        exchange: &Exchange,
+        periods: Vec<Period>,
+        filters: &[Filter],
+    ) -> anyhow::Result<Report> {
+        let filters: Vec<&Filter> = filters.iter().collect();
+
+        let reports = general::Report::periods(currency, config, exchange, &filters)?;
+
+        let mut totals: HashMap<String, i64> = HashMap::new();
+
+        for report in reports.iter() {
+            for item in report.items() {
+                *totals.entry(item.category().to_string()).or_default() += item.value().cents();
+            }
+        }
+
+        let mut categories: Vec<String> = totals.keys().cloned().collect();
+
+        categories.sort_by_key(|category| (-totals[category].abs(), category.to_string()));
+
+        Ok(Self {
+            currency,
+            periods,
+            reports,
+            categories,
+        })
+    }
+
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+
+    /// Categories with any transaction in the periods, sorted by the absolute value of their
+    /// amount in all of them.
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// Amount of the category on the period (by index).
+    pub fn value(&self, category: &str, period: usize) -> Money {
+        self.reports[period]
+            .items()
+            .iter()
+            .find(|item| item.category() == category)
+            .map(|item| item.value())
+            .unwrap_or_else(|| Money::new(self.currency, 0))
+    }
+
+    /// Difference of the amount of the category to the one of the previous period (none for the
+    /// first period).
+    pub fn delta(&self, category: &str, period: usize) -> Option<Money> {
+        let previous = period.checked_sub(1)?;
+
+        Some(self.difference(self.value(category, period), self.value(category, previous)))
+    }
+
+    /// Total of the period (by index).
+    pub fn total(&self, period: usize) -> Money {
+        self.reports[period].total()
+    }
+
+    /// Difference of the total to the one of the previous period (none for the first period).
+    pub fn total_delta(&self, period: usize) -> Option<Money> {
+        let previous = period.checked_sub(1)?;
+
+        Some(self.difference(self.total(period), self.total(previous)))
+    }
+
+    fn difference(&self, value: Money, previous: Money) -> Money {
+        Money::new(self.currency, value.cents() - previous.cents())
+    }
+
+    pub fn display(&self) {
+        let mut table = Table::new();
+
+        table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+        let columns = self.periods.len() * 2;
+
+        table.set_titles(Row::new(vec![Cell::new("Comparison")
+            .with_hspan(columns)
+            .style_spec("bcFC")]));
+
+        let mut headers = vec![Cell::new("Category").style_spec("bcFB")];
+
+        for (index, period) in self.periods.iter().enumerate() {
+            if index > 0 {
+                headers.push(Cell::new("Δ").style_spec("bFB"));
+            }
+
+            headers.push(Cell::new(&period.label).style_spec("bFB"));
+        }
+
+        table.add_row(Row::new(headers));
+
+        for category in self.categories.iter() {
+            let values = (0..self.periods.len())
+                .map(|index| (self.value(category, index), self.delta(category, index)))
+                .collect();
+
+            table.add_row(Self::row(category, values, "bFW"));
+        }
+
+        let totals = (0..self.periods.len())
+            .map(|index| (self.total(index), self.total_delta(index)))
+            .collect();
+
+        table.add_row(Self::row("Total", totals, "bFY"));
+
+        table.printstd();
+    }
+
+    fn row(name: &str, values: Vec<(Money, Option<Money>)>, style: &str) -> Row {
+        let mut cells = vec![Cell::new(name).style_spec(style)];
+
+        for (value, delta) in values.iter() {
+            if let Some(delta) = delta {
+                cells.push(util::money_cell(
+                    delta,
+                    true,
+                    false,
+                    format::Alignment::LEFT,
+                ));
+            }
+
+            cells.push(Cell::new(&format!("{}", value)).style_spec(style));
+        }
+
+        Row::new(cells)
+    }
+
+    // Values of a row of the sections, in the order of the headers.
+    fn cells(name: &str, values: Vec<(Money, Option<Money>)>) -> Vec<String> {
+        let mut cells = vec![name.to_string()];
+
+        for (value, delta) in values.iter() {
+            if let Some(delta) = delta {
+                cells.push(delta.to_storage());
+            }
+
+            cells.push(value.to_storage());
+        }
+
+        cells
+    }
+
+    // Headers of the sections: the label of each period, preceded by the difference to the
+    // previous one (as in the table).
+    fn headers(&self) -> Vec<String> {
+        let mut headers = vec!["Category".to_string()];
+
+        for (index, period) in self.periods.iter().enumerate() {
+            if index > 0 {
+                headers.push(format!("Δ {}", period.label));
+            }
+
+            headers.push(period.label.to_string());
+        }
+
+        headers
+    }
+}
+
+#[derive(Serialize)]
+struct CategoryOutput<'a> {
+    category: &'a str,
+    values: Vec<Money>,
+    deltas: Vec<Option<Money>>,
+}
+
+impl Serialize for Report {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let indexes = 0..self.periods.len();
+
+        let categories: Vec<CategoryOutput> = self
+            .categories
+            .iter()
+            .map(|category| CategoryOutput {
+                category,
+                values: indexes
+                    .clone()
+                    .map(|index| self.value(category, index))
+                    .collect(),
+                deltas: indexes
+                    .clone()
+                    .map(|index| self.delta(category, index))
+                    .collect(),
+            })
+            .collect();
+
+        let totals: Vec<Money> = indexes.clone().map(|index| self.total(index)).collect();
+
+        let total_deltas: Vec<Option<Money>> =
+            indexes.map(|index| self.total_delta(index)).collect();
+
+        let mut state = serializer.serialize_struct("Report", 5)?;
+        state.serialize_field("currency", &self.currency)?;
+        state.serialize_field("periods", &self.periods)?;
+        state.serialize_field("categories", &categories)?;
+        state.serialize_field("totals", &totals)?;
+        state.serialize_field("total_deltas", &total_deltas)?;
+        state.end()
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let headers = self.headers();
+        let headers: Vec<&str> = headers.iter().map(|header| header.as_str()).collect();
+
+        let mut section = Section::new("Comparison", &headers);
+
+        for category in self.categories.iter() {
+            let values = (0..self.periods.len())
+                .map(|index| (self.value(category, index), self.delta(category, index)))
+                .collect();
+
+            section.rows.push(Self::cells(category, values));
+        }
+
+        let totals = (0..self.periods.len())
+            .map(|index| (self.total(index), self.total_delta(index)))
+            .collect();
+
+        section.rows.push(Self::cells("Total", totals));
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self);
+
+        Ok(())
+    }
+}
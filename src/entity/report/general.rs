@@ -1,16 +1,20 @@
 use prettytable::{format, Cell, Row, Table};
+use serde::ser::{Error, SerializeStruct};
+use serde::{Serialize, Serializer};
 
 use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::ops::AddAssign;
 
-use crate::cmd::report::Args;
 use crate::config::Config;
+use crate::entity::budget::{self, Budgets};
 use crate::entity::line::{Line, Liner};
 use crate::entity::money::{Currency, Money};
 use crate::entity::total::Total;
+use crate::entity::transfer::{Pair, Transfers};
 use crate::exchange::Exchange;
 use crate::filter::Filter;
+use crate::output::{Render, Section};
 use crate::resource::Resource;
 use crate::{util, Mode};
 
@@ -23,88 +27,193 @@ pub struct Report {
     items: HashMap<String, Item>,
     occurrences: u32,
     total: i64,
-    previous: Option<Line>,
+    budgets: Budgets,
 }
 
 impl Report {
-    fn title() -> Row {
-        Row::new(vec![Cell::new("Report").with_hspan(4).style_spec("bcFC")])
+    fn title(&self) -> Row {
+        let span = if self.budgets.is_empty() { 4 } else { 7 };
+
+        Row::new(vec![Cell::new("Report")
+            .with_hspan(span)
+            .style_spec("bcFC")])
     }
 
-    fn headers() -> Row {
-        Row::new(vec![
+    fn headers(&self) -> Row {
+        let mut cells = vec![
             Cell::new("Category").style_spec("bcFB").with_hspan(2),
             Cell::new("Amount").style_spec("bFB"),
             Cell::new("(%)").style_spec("bFB"),
-        ])
+        ];
+
+        if !self.budgets.is_empty() {
+            cells.push(Cell::new("Budget").style_spec("bFB"));
+            cells.push(Cell::new("Remaining").style_spec("bFB"));
+            cells.push(Cell::new("(%)").style_spec("bFB"));
+        }
+
+        Row::new(cells)
     }
 
     pub fn new(
-        args: &Args,
+        currency: Currency,
         total: &mut Total,
         config: &Config,
         exchange: &Exchange,
         filter: &Filter,
     ) -> anyhow::Result<Report> {
-        let mut report = Self {
-            currency: util::currency(args.currency.as_ref(), config)?,
-            ..Default::default()
-        };
+        let mut reports = Report::build(currency, Some(total), config, exchange, &[filter])?;
+
+        Ok(reports.remove(0))
+    }
+
+    /// Reports for several periods (one per filter) at once, reading the ledger a single time.
+    pub fn periods(
+        currency: Currency,
+        config: &Config,
+        exchange: &Exchange,
+        filters: &[&Filter],
+    ) -> anyhow::Result<Vec<Report>> {
+        Report::build(currency, None, config, exchange, filters)
+    }
+
+    fn build(
+        currency: Currency,
+        mut total: Option<&mut Total>,
+        config: &Config,
+        exchange: &Exchange,
+        filters: &[&Filter],
+    ) -> anyhow::Result<Vec<Report>> {
+        let mut reports = filters
+            .iter()
+            .map(|filter| {
+                Ok(Self {
+                    currency,
+                    budgets: Budgets::new(config, currency, exchange, filter.start, filter.end)?,
+                    ..Default::default()
+                })
+            })
+            .collect::<anyhow::Result<Vec<Report>>>()?;
 
         let mut resource = Resource::new(config, Mode::Ledger)?;
 
-        resource.line(&mut |record| {
-            total.sum(record, exchange)?;
+        let mut transfers = Transfers::new();
 
-            if !filter.within(record.date()) {
-                return Ok(());
+        resource.line(&mut |record| {
+            if let Some(total) = total.as_deref_mut() {
+                total.sum(record, exchange)?;
             }
 
-            if filter.transfer(&record.category()) {
-                match report.previous.take() {
-                    None => report.previous = Some(record.clone()),
-                    Some(mut val) => {
-                        if filter.accountable(&record.account())
-                            ^ filter.accountable(&val.account())
-                        {
-                            // Set the category as the destination/source account to not show all
-                            // transfers with the default category for transfers.
-                            report.process(record, val.account(), filter, exchange)?;
-                            report.process(&mut val, record.account(), filter, exchange)?;
-                        }
+            if record.category() == config.transfer {
+                // Legs are paired before filtering by date, so that a leg is never paired with
+                // the one of another transfer.
+                if let Some(pair) = transfers.pair(record, exchange) {
+                    for (report, filter) in reports.iter_mut().zip(filters) {
+                        report.transfer(&pair, filter, exchange)?;
                     }
                 }
             } else {
-                report.process(record, record.category(), filter, exchange)?;
+                for (report, filter) in reports.iter_mut().zip(filters) {
+                    if filter.within(record.date()) {
+                        report.process(record, record.category(), filter, exchange)?;
+                    }
+                }
             };
 
             Ok(())
         })?;
 
-        Ok(report)
+        for line in transfers.pending() {
+            log::warn!(
+                "The transfer '{}' of '{}' on {} has a single leg",
+                line.transfer(),
+                line.account(),
+                line.date()
+            );
+        }
+
+        Ok(reports)
     }
 
-    pub fn display(&self) {
+    pub fn display(&self) -> anyhow::Result<()> {
         let mut table = Table::new();
 
         table.set_format(format::FormatBuilder::new().padding(2, 3).build());
 
-        table.set_titles(Report::title());
+        table.set_titles(self.title());
 
-        table.add_row(Report::headers());
+        table.add_row(self.headers());
 
         for item in self.sorted() {
-            table.add_row(item.row(self));
+            table.add_row(item.row(self)?);
         }
 
         table.add_row(self.row());
 
         table.printstd();
+
+        Ok(())
+    }
+
+    /// Categories of the report, sorted by the absolute value of their amount.
+    pub fn items(&self) -> Vec<Item> {
+        self.sorted()
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn income(&self) -> Money {
+        Money::new(self.currency, self.income)
+    }
+
+    pub fn expense(&self) -> Money {
+        Money::new(self.currency, self.expense)
+    }
+
+    pub fn excluded(&self) -> Money {
+        Money::new(self.currency, self.excluded)
+    }
+
+    pub fn occurrences(&self) -> u32 {
+        self.occurrences
+    }
+
+    /// Budgets for the period of the report (none if the period has no bounds).
+    pub fn budgets(&self) -> &Budgets {
+        &self.budgets
+    }
+
+    fn transfer(
+        &mut self,
+        pair: &Pair,
+        filter: &Filter,
+        exchange: &Exchange,
+    ) -> anyhow::Result<()> {
+        let (source, destination) = (pair.source.account(), pair.destination.account());
+
+        // Only transfers from/to accounts that are not accountable change the totals.
+        if !(filter.accountable(&source) ^ filter.accountable(&destination)) {
+            return Ok(());
+        }
+
+        // Set the category as the destination/source account to not show all transfers with the
+        // default category for transfers.
+        if filter.within(pair.source.date()) {
+            self.process(&pair.source, destination, filter, exchange)?;
+        }
+
+        if filter.within(pair.destination.date()) {
+            self.process(&pair.destination, source, filter, exchange)?;
+        }
+
+        Ok(())
     }
 
     fn process(
         &mut self,
-        record: &mut Line,
+        record: &Line,
         category: String,
         filter: &Filter,
         exchange: &Exchange,
@@ -121,12 +230,12 @@ impl Report {
             return Ok(());
         };
 
-        self.add(Item::new(exchanged.amount(), category));
+        self.add(Item::new(exchanged.amount(), category))?;
 
         Ok(())
     }
 
-    fn add(&mut self, item: Item) {
+    fn add(&mut self, item: Item) -> crate::Result<()> {
         if item.value.positive() {
             self.income += item.value.cents();
         } else {
@@ -136,10 +245,14 @@ impl Report {
         self.total += item.value.cents();
         self.occurrences += 1;
 
-        self.items
-            .entry(item.category.to_string())
-            .and_modify(|i| *i += item.clone())
-            .or_insert(item);
+        match self.items.entry(item.category.to_string()) {
+            Entry::Occupied(mut existing) => existing.get_mut().add(item)?,
+            Entry::Vacant(entry) => {
+                entry.insert(item);
+            }
+        };
+
+        Ok(())
     }
 
     fn sorted(&self) -> Vec<Item> {
@@ -150,11 +263,12 @@ impl Report {
         values
     }
 
-    fn total(&self) -> Money {
+    pub fn total(&self) -> Money {
         Money::new(self.currency, self.total)
     }
 
-    fn percentage(&self) -> f64 {
+    /// Share of the income that was saved (or overspent, if expenses exceed it).
+    pub fn percentage(&self) -> f64 {
         let expense = self.expense.abs();
 
         if self.income == 0 {
@@ -177,7 +291,7 @@ impl Report {
 }
 
 #[derive(Debug, Clone)]
-struct Item {
+pub struct Item {
     category: String,
     value: Money,
     occurrences: u32,
@@ -192,7 +306,35 @@ impl Item {
         }
     }
 
-    fn percentage(&self, report: &Report) -> f64 {
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    pub fn value(&self) -> Money {
+        self.value
+    }
+
+    pub fn occurrences(&self) -> u32 {
+        self.occurrences
+    }
+
+    fn add(&mut self, other: Item) -> crate::Result<()> {
+        self.value = self.value.checked_add(other.value)?;
+        self.occurrences += 1;
+
+        Ok(())
+    }
+
+    /// Budget of the category (if any) and what is left of it.
+    fn budget(&self, report: &Report) -> crate::Result<Option<(Money, Money)>> {
+        match report.budgets.get(&self.category) {
+            Some(amount) => Ok(Some((amount, budget::remaining(amount, self.value)?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Share of the report's income (or expense) represented by this category.
+    pub fn percentage(&self, report: &Report) -> f64 {
         if self.value.positive() {
             (self.value.cents() as f64) / (report.income as f64) * 100.0
         } else {
@@ -200,23 +342,35 @@ impl Item {
         }
     }
 
-    fn row(&self, report: &Report) -> Row {
-        Row::new(vec![
+    fn row(&self, report: &Report) -> crate::Result<Row> {
+        let mut cells = vec![
             Cell::new(&format!("({})", self.occurrences)).style_spec("bFW"),
             Cell::new(&self.category).style_spec("bFW"),
             Cell::new(&format!("{}", self.value)).style_spec("bFW"),
             Cell::new(&format!("{:.2}", self.percentage(report))).style_spec("bFW"),
-        ])
-    }
-}
-
-impl AddAssign for Item {
-    fn add_assign(&mut self, other: Self) {
-        *self = Self {
-            category: self.category.to_string(),
-            value: self.value + other.value,
-            occurrences: self.occurrences + 1,
+        ];
+
+        if !report.budgets.is_empty() {
+            match self.budget(report)? {
+                Some((amount, remaining)) => {
+                    cells.push(Cell::new(&format!("{}", amount)).style_spec("bFW"));
+                    cells.push(util::money_cell(
+                        &remaining,
+                        true,
+                        false,
+                        format::Alignment::LEFT,
+                    ));
+                    cells.push(util::consumption_cell(
+                        &self.value,
+                        &amount,
+                        format::Alignment::LEFT,
+                    ));
+                }
+                None => cells.extend(vec![Cell::new(""), Cell::new(""), Cell::new("")]),
+            }
         }
+
+        Ok(Row::new(cells))
     }
 }
 
@@ -286,7 +440,7 @@ impl Summary {
         table.printstd();
     }
 
-    fn income(&self) -> Money {
+    pub fn income(&self) -> Money {
         let value = if self.excluded < 0 && self.income > self.excluded.abs() {
             self.income + self.excluded
         } else {
@@ -296,12 +450,21 @@ impl Summary {
         Money::new(self.currency, value)
     }
 
-    fn expense(&self) -> Money {
+    pub fn expense(&self) -> Money {
         Money::new(self.currency, self.expense)
     }
 
-    fn difference(&self) -> Money {
-        self.income() - self.expense().abs()
+    pub fn difference(&self) -> Money {
+        // Both income and expense are always in the currency of the report.
+        Money::new(
+            self.currency,
+            self.income().cents() - self.expense().abs().cents(),
+        )
+    }
+
+    /// Balance of all accountable accounts at the end of the period.
+    pub fn balance(&self) -> Money {
+        self.total.amount()
     }
 
     fn row(&self) -> Row {
@@ -327,3 +490,127 @@ impl Summary {
         ])
     }
 }
+
+#[derive(Serialize)]
+struct ItemOutput {
+    category: String,
+    amount: Money,
+    occurrences: u32,
+    percentage: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining: Option<Money>,
+}
+
+impl Report {
+    fn output(&self) -> crate::Result<Vec<ItemOutput>> {
+        self.sorted()
+            .iter()
+            .map(|item| {
+                let budget = item.budget(self)?;
+
+                Ok(ItemOutput {
+                    category: item.category.to_string(),
+                    amount: item.value,
+                    occurrences: item.occurrences,
+                    percentage: item.percentage(self),
+                    budget: budget.map(|(amount, _)| amount),
+                    remaining: budget.map(|(_, remaining)| remaining),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Serialize for Report {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Report", 5)?;
+        state.serialize_field("currency", &self.currency)?;
+        state.serialize_field("items", &self.output().map_err(S::Error::custom)?)?;
+        state.serialize_field("total", &self.total())?;
+        state.serialize_field("occurrences", &self.occurrences)?;
+        state.serialize_field("percentage", &self.percentage())?;
+        state.end()
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut headers = vec!["Category", "Occurrences", "Amount", "Percentage"];
+
+        if !self.budgets.is_empty() {
+            headers.extend(["Budget", "Remaining"]);
+        }
+
+        let mut section = Section::new("Report", &headers);
+
+        for item in self.output()? {
+            let mut row = vec![
+                item.category,
+                item.occurrences.to_string(),
+                item.amount.to_storage(),
+                format!("{:.2}", item.percentage),
+            ];
+
+            if !self.budgets.is_empty() {
+                row.push(item.budget.map(|v| v.to_storage()).unwrap_or_default());
+                row.push(item.remaining.map(|v| v.to_storage()).unwrap_or_default());
+            }
+
+            section.rows.push(row);
+        }
+
+        section.rows.push(vec![
+            "Total".to_string(),
+            self.occurrences.to_string(),
+            self.total().to_storage(),
+            format!("{:.2}", self.percentage()),
+        ]);
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self)
+    }
+}
+
+impl Serialize for Summary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Summary", 5)?;
+        state.serialize_field("currency", &self.currency)?;
+        state.serialize_field("income", &self.income())?;
+        state.serialize_field("expense", &self.expense())?;
+        state.serialize_field("difference", &self.difference())?;
+        state.serialize_field("balance", &self.balance())?;
+        state.end()
+    }
+}
+
+impl Render for Summary {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Totals", &["Income", "Expense", "Difference", "Balance"]);
+
+        section.rows.push(vec![
+            self.income().to_storage(),
+            self.expense().to_storage(),
+            self.difference().to_storage(),
+            self.balance().to_storage(),
+        ]);
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Summary::display(self);
+
+        Ok(())
+    }
+}
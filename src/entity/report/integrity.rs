@@ -0,0 +1,250 @@
+use prettytable::{format, Cell, Row, Table};
+use serde::Serialize;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::entity::money::Currency;
+use crate::entity::transfer::Transfers;
+use crate::exchange::Exchange;
+use crate::output::{Render, Section};
+use crate::resource::Resource;
+use crate::Mode;
+
+/// Problems found in the ledger and networth files, by line.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    issues: Vec<Issue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub file: &'static str,
+    pub line: u64,
+    pub description: String,
+}
+
+impl Report {
+    fn title() -> Row {
+        Row::new(vec![Cell::new("Check").with_hspan(3).style_spec("bcFC")])
+    }
+
+    fn headers() -> Row {
+        Row::new(vec![
+            Cell::new("File").style_spec("bcFB"),
+            Cell::new("Line").style_spec("bcFB"),
+            Cell::new("Problem").style_spec("bFB"),
+        ])
+    }
+
+    pub fn new(config: &Config, exchange: &Exchange) -> anyhow::Result<Report> {
+        let mut report = Self::default();
+
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        report.ledger(config, exchange, resource.records()?);
+
+        let mut resource = Resource::new(config, Mode::Networth)?;
+
+        report.networth(resource.records()?);
+
+        Ok(report)
+    }
+
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    pub fn display(&self) {
+        let mut table = Table::new();
+
+        table.set_format(format::FormatBuilder::new().padding(2, 3).build());
+
+        table.set_titles(Report::title());
+
+        table.add_row(Report::headers());
+
+        for issue in self.issues.iter() {
+            table.add_row(Row::new(vec![
+                Cell::new(issue.file).style_spec("bFW"),
+                Cell::new(&issue.line.to_string()).style_spec("bFW"),
+                Cell::new(&issue.description).style_spec("bFR"),
+            ]));
+        }
+
+        table.printstd();
+    }
+
+    fn ledger(
+        &mut self,
+        config: &Config,
+        exchange: &Exchange,
+        records: Vec<(u64, anyhow::Result<Line>)>,
+    ) {
+        let file = "ledger";
+
+        let mut seen: HashMap<String, u64> = HashMap::new();
+        let mut currencies: HashMap<String, (Currency, u64)> = HashMap::new();
+        let mut transfers = Transfers::new();
+        let mut legs: HashMap<String, u64> = HashMap::new();
+        let mut previous: Option<Date> = None;
+
+        let categories: HashSet<&String> = config.categories.iter().collect();
+
+        for (number, record) in records {
+            let line = match record {
+                Ok(value) => value,
+                Err(e) => {
+                    self.add(file, number, e.to_string());
+                    continue;
+                }
+            };
+
+            self.dates(file, number, line.date(), &mut previous, false);
+
+            let key = format!(
+                "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+                line.account(),
+                line.date(),
+                line.category(),
+                line.description(),
+                line.quantity(),
+                line.venue(),
+                line.amount().to_storage(),
+                line.trip(),
+                line.transfer()
+            );
+
+            if let Some(original) = seen.get(&key) {
+                self.add(file, number, format!("Duplicate of line {}", original));
+            } else {
+                seen.insert(key, number);
+            }
+
+            match currencies.get(&line.account()) {
+                Some((currency, original)) if *currency != line.currency() => self.add(
+                    file,
+                    number,
+                    format!(
+                        "'{}' is in {} but it was in {} on line {}",
+                        line.account(),
+                        line.currency().code(),
+                        currency.code(),
+                        original
+                    ),
+                ),
+                Some(_) => {}
+                None => {
+                    currencies.insert(line.account(), (line.currency(), number));
+                }
+            }
+
+            let category = line.category();
+
+            if category == config.transfer {
+                legs.entry(line.transfer()).or_insert(number);
+
+                if let Some(e) = transfers.add(&line, exchange).problem {
+                    self.add(file, number, e.to_string());
+                }
+            } else if category == config.investments {
+                if line.quantity().parse::<i64>().is_err() {
+                    self.add(
+                        file,
+                        number,
+                        format!("The quantity '{}' is not a whole number", line.quantity()),
+                    );
+                }
+            } else if !categories.is_empty() && !categories.contains(&category) {
+                self.add(file, number, format!("Unknown category '{}'", category));
+            }
+        }
+
+        for line in transfers.pending() {
+            let number = legs.get(&line.transfer()).copied().unwrap_or_default();
+
+            self.add(
+                file,
+                number,
+                format!("The transfer '{}' has a single leg", line.transfer()),
+            );
+        }
+
+        self.issues.sort_by_key(|issue| (issue.file, issue.line));
+    }
+
+    fn networth(&mut self, records: Vec<(u64, anyhow::Result<Line>)>) {
+        let file = "networth";
+
+        let mut previous: Option<Date> = None;
+
+        for (number, record) in records {
+            match record {
+                // There is a single entry per day, so dates must always increase.
+                Ok(line) => self.dates(file, number, line.date(), &mut previous, true),
+                Err(e) => self.add(file, number, e.to_string()),
+            }
+        }
+    }
+
+    fn dates(
+        &mut self,
+        file: &'static str,
+        number: u64,
+        date: Date,
+        previous: &mut Option<Date>,
+        strict: bool,
+    ) {
+        // Future dates are not taken into account for the order, otherwise all the lines after
+        // one of them would be reported too.
+        if date.future() {
+            return self.add(file, number, format!("The date {} is in the future", date));
+        }
+
+        match previous {
+            Some(value) if date < *value => self.add(
+                file,
+                number,
+                format!("The date {} is out of order (after {})", date, value),
+            ),
+            Some(value) if strict && date == *value => self.add(
+                file,
+                number,
+                format!("There is more than one entry on {}", date),
+            ),
+            _ => *previous = Some(date),
+        }
+    }
+
+    fn add(&mut self, file: &'static str, line: u64, description: String) {
+        self.issues.push(Issue {
+            file,
+            line,
+            description,
+        });
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let mut section = Section::new("Check", &["File", "Line", "Problem"]);
+
+        for issue in self.issues.iter() {
+            section.rows.push(vec![
+                issue.file.to_string(),
+                issue.line.to_string(),
+                issue.description.to_string(),
+            ]);
+        }
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self);
+
+        Ok(())
+    }
+}
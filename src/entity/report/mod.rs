@@ -1,3 +1,5 @@
 pub mod check;
+pub mod comparison;
 pub mod general;
+pub mod integrity;
 pub mod networth;
@@ -1,5 +1,7 @@
 use prettytable::format::{Alignment, FormatBuilder};
 use prettytable::{color, Attr, Cell, Row, Table};
+use serde::ser::{Error, SerializeStruct};
+use serde::{Serialize, Serializer};
 
 use std::collections::BTreeMap;
 
@@ -9,6 +11,8 @@ use crate::entity::line::{Line, Liner};
 use crate::entity::money::{Currency, Money};
 use crate::entity::networth::Networth;
 use crate::exchange::Exchange;
+use crate::output::{Render, Section};
+use crate::price::Prices;
 use crate::resource::Resource;
 use crate::{util, Mode};
 
@@ -33,15 +37,30 @@ impl Report {
         ])
     }
 
-    pub fn new(config: Config, exchange: Exchange, currency: Currency) -> anyhow::Result<Report> {
+    pub fn new(
+        config: Config,
+        exchange: Exchange,
+        prices: &mut Prices,
+        currency: Currency,
+    ) -> anyhow::Result<Report> {
         Ok(Self {
-            networth: Networth::new(&config, &exchange, currency)?,
+            networth: Networth::new(&config, &exchange, prices, currency)?,
             exchange,
             config,
         })
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
+        // Storing a networth without some of the investments would show up as a (false) loss.
+        if let Some(error) = self
+            .networth
+            .unavailable()
+            .first()
+            .and_then(|investment| investment.error.clone())
+        {
+            return Err(error.into());
+        }
+
         let mut resource = Resource::new(&self.config, Mode::Networth)?;
 
         let entries = self.entries(&mut resource)?;
@@ -61,7 +80,9 @@ impl Report {
         Ok(())
     }
 
-    pub fn display(&self) {
+    pub fn display(&self) -> anyhow::Result<()> {
+        let total = self.networth.total()?;
+
         let mut table = Table::new();
 
         table.set_format(FormatBuilder::new().padding(0, 3).build());
@@ -71,22 +92,40 @@ impl Report {
         table.add_row(Report::headers());
 
         for investment in self.networth.investments.values() {
-            if investment.value().zero() {
+            if investment.quantity == 0 {
                 continue;
             };
 
             let color = Attr::ForegroundColor(color::BRIGHT_WHITE);
 
-            table.add_row(Row::new(vec![
-                Cell::new_align(&investment.quantity.to_string(), Alignment::RIGHT)
+            // Prices that could not be updated are flagged with the date they are from (or the
+            // reason why there is no price at all).
+            let name = if let Some(error) = &investment.error {
+                Cell::new(&error.to_string())
                     .with_style(Attr::Bold)
-                    .with_style(color),
+                    .with_style(Attr::ForegroundColor(color::BRIGHT_RED))
+            } else if let Some(asset) = investment.asset.as_ref().filter(|v| v.stale) {
+                Cell::new(&format!(
+                    "{} (stale since {})",
+                    asset.name,
+                    asset.fetched.format("%Y-%m-%d")
+                ))
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::BRIGHT_RED))
+            } else {
                 Cell::new(&investment.name())
+                    .with_style(Attr::Bold)
+                    .with_style(color)
+            };
+
+            table.add_row(Row::new(vec![
+                Cell::new_align(&investment.quantity.to_string(), Alignment::RIGHT)
                     .with_style(Attr::Bold)
                     .with_style(color),
+                name,
                 util::money_cell(&investment.value(), true, false, Alignment::LEFT)
                     .with_style(color),
-                util::percentage_cell(&investment.value(), &self.networth.total(), Alignment::LEFT)
+                util::percentage_cell(&investment.value(), &total, Alignment::LEFT)
                     .with_style(color),
             ]));
         }
@@ -99,12 +138,14 @@ impl Report {
             Cell::new(""),
             Cell::new("Cash").with_style(Attr::Bold).with_style(color),
             util::money_cell(&cash, true, false, Alignment::LEFT).with_style(color),
-            util::percentage_cell(&cash, &self.networth.total(), Alignment::LEFT).with_style(color),
+            util::percentage_cell(&cash, &total, Alignment::LEFT).with_style(color),
         ]));
 
-        table.add_row(self.row());
+        table.add_row(self.row(&total));
 
         table.printstd();
+
+        Ok(())
     }
 
     fn entries(&self, resource: &mut Resource) -> anyhow::Result<BTreeMap<Date, Line>> {
@@ -114,22 +155,25 @@ impl Report {
             let mut exchanged = record.exchange(self.networth.currency, &self.exchange)?;
 
             exchanged.set_invested(self.networth.invested_on(exchanged.date()));
-            exchanged
-                .set_amount(self.networth.current_on(exchanged.date()) + exchanged.investment());
+            exchanged.set_amount(
+                self.networth
+                    .current_on(exchanged.date())
+                    .checked_add(exchanged.investment())?,
+            );
 
             result.entry(exchanged.date()).or_insert(exchanged);
 
             Ok(())
         })?;
 
-        let current = self.networth.current();
+        let current = self.networth.current()?;
 
         result.entry(current.date()).or_insert(current);
 
         Ok(result)
     }
 
-    fn row(&self) -> Row {
+    fn row(&self, total: &Money) -> Row {
         let color = Attr::ForegroundColor(color::BRIGHT_YELLOW);
 
         let money = Money::new(self.networth.currency, 1);
@@ -137,9 +181,118 @@ impl Report {
         Row::new(vec![
             Cell::new(""),
             Cell::new("Total").with_style(Attr::Bold).with_style(color),
-            util::money_cell(&self.networth.total(), true, false, Alignment::LEFT)
-                .with_style(color),
+            util::money_cell(total, true, false, Alignment::LEFT).with_style(color),
             util::percentage_cell(&money, &money, Alignment::LEFT).with_style(color),
         ])
     }
 }
+
+#[derive(Serialize)]
+struct InvestmentOutput {
+    code: String,
+    name: String,
+    quantity: i64,
+    amount: Money,
+    percentage: f64,
+    stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Report {
+    fn output(&self, total: &Money) -> Vec<InvestmentOutput> {
+        self.networth
+            .investments
+            .values()
+            .filter(|investment| investment.quantity != 0)
+            .map(|investment| InvestmentOutput {
+                code: investment.code.to_string(),
+                name: investment.name(),
+                quantity: investment.quantity,
+                amount: investment.value(),
+                percentage: Report::percentage(&investment.value(), total),
+                stale: investment.stale(),
+                error: investment.error.as_ref().map(|error| error.to_string()),
+            })
+            .collect()
+    }
+
+    fn percentage(value: &Money, total: &Money) -> f64 {
+        if total.zero() {
+            0.0
+        } else {
+            (value.cents() as f64 / total.cents() as f64) * 100.0
+        }
+    }
+}
+
+impl Serialize for Report {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let total = self.networth.total().map_err(S::Error::custom)?;
+
+        let mut state = serializer.serialize_struct("Report", 4)?;
+        state.serialize_field("currency", &self.networth.currency)?;
+        state.serialize_field("investments", &self.output(&total))?;
+        state.serialize_field("cash", &self.networth.current_on(Date::today()))?;
+        state.serialize_field("total", &total)?;
+        state.end()
+    }
+}
+
+impl Render for Report {
+    fn sections(&self) -> anyhow::Result<Vec<Section>> {
+        let total = self.networth.total()?;
+
+        let mut section = Section::new(
+            "Networth",
+            &[
+                "Code",
+                "Name",
+                "Shares",
+                "Amount",
+                "Percentage",
+                "Stale",
+                "Error",
+            ],
+        );
+
+        for investment in self.output(&total) {
+            section.rows.push(vec![
+                investment.code,
+                investment.name,
+                investment.quantity.to_string(),
+                investment.amount.to_storage(),
+                format!("{:.2}", investment.percentage),
+                investment.stale.to_string(),
+                investment.error.unwrap_or_default(),
+            ]);
+        }
+
+        let cash = self.networth.current_on(Date::today());
+
+        section.rows.push(vec![
+            String::new(),
+            "Cash".to_string(),
+            String::new(),
+            cash.to_storage(),
+            format!("{:.2}", Report::percentage(&cash, &total)),
+        ]);
+
+        section.rows.push(vec![
+            String::new(),
+            "Total".to_string(),
+            String::new(),
+            total.to_storage(),
+            format!("{:.2}", 100.0),
+        ]);
+
+        Ok(vec![section])
+    }
+
+    fn display(&self) -> anyhow::Result<()> {
+        Report::display(self)
+    }
+}
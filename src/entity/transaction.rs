@@ -8,7 +8,7 @@ use crate::entity::line::{Line, Liner};
 use crate::entity::money::{Currency, Money};
 use crate::exchange::Exchange;
 
-pub static FIELDS: [&str; 10] = [
+pub static FIELDS: [&str; 11] = [
     "Account",
     "Date",
     "Category",
@@ -19,6 +19,7 @@ pub static FIELDS: [&str; 10] = [
     "Currency",
     "Trip",
     "Exported",
+    "Transfer",
 ];
 
 #[derive(Clone, Debug, Serialize, Default)]
@@ -34,6 +35,8 @@ pub struct Transaction {
     pub currency: Currency,
     pub trip: String,
     pub exported: String,
+    /// Identifier shared by both legs of a transfer (empty for any other transaction).
+    pub transfer: String,
 }
 
 impl Transaction {
@@ -51,6 +54,7 @@ impl Transaction {
             currency,
             trip: values[8].to_string(),
             exported: values[9].to_string(),
+            transfer: values.get(10).cloned().unwrap_or_default(),
         })
     }
 }
@@ -100,15 +104,23 @@ impl Liner for Transaction {
         self.exported.to_string()
     }
 
+    fn transfer(&self) -> String {
+        self.transfer.to_string()
+    }
+
     fn set_exported(&mut self, value: String) {
         self.exported = value;
     }
 
+    fn set_transfer(&mut self, value: String) {
+        self.transfer = value;
+    }
+
     fn set_invested(&mut self, _value: Money) {}
     fn set_amount(&mut self, _value: Money) {}
 
     fn exchange(&self, to: Currency, exchange: &Exchange) -> anyhow::Result<Line> {
-        let money = self.amount.exchange(to, exchange)?;
+        let money = self.amount.exchange(to, exchange, self.date)?;
 
         Ok(Transaction {
             account: self.account.to_string(),
@@ -121,6 +133,7 @@ impl Liner for Transaction {
             amount: money,
             trip: self.trip.to_string(),
             exported: self.exported.to_string(),
+            transfer: self.transfer.to_string(),
         }
         .into())
     }
@@ -148,6 +161,7 @@ impl<'de> Deserialize<'de> for Transaction {
             Currency,
             Trip,
             Exported,
+            Transfer,
         }
 
         struct TransactionVisitor;
@@ -173,6 +187,7 @@ impl<'de> Deserialize<'de> for Transaction {
                 let mut currency = None;
                 let mut trip = None;
                 let mut exported = None;
+                let mut transfer = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -236,6 +251,12 @@ impl<'de> Deserialize<'de> for Transaction {
                             }
                             exported = Some(map.next_value()?);
                         }
+                        Field::Transfer => {
+                            if transfer.is_some() {
+                                return Err(de::Error::duplicate_field("transfer"));
+                            }
+                            transfer = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -254,6 +275,8 @@ impl<'de> Deserialize<'de> for Transaction {
                     currency,
                     trip: trip.ok_or_else(|| de::Error::missing_field("trip"))?,
                     exported: exported.ok_or_else(|| de::Error::missing_field("exported"))?,
+                    // Files created before transfers had an identifier do not have this column.
+                    transfer: transfer.unwrap_or_default(),
                 })
             }
         }
@@ -269,6 +292,7 @@ impl<'de> Deserialize<'de> for Transaction {
             "currency",
             "trip",
             "exported",
+            "transfer",
         ];
         deserializer.deserialize_struct("Transaction", FIELDS, TransactionVisitor)
     }
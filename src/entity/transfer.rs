@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::error::Error;
+use crate::exchange::Exchange;
+
+// Transfers between currencies are booked with the rate of the bank, which never matches exactly
+// the reference rate used to exchange the legs (5% of the amount transferred is allowed).
+const TOLERANCE: f64 = 0.05;
+
+/// Pairs the legs of transfers by their identifier (the `Transfer` column), regardless of where
+/// they are in the ledger.
+#[derive(Debug, Default)]
+pub struct Transfers {
+    pending: HashMap<String, Line>,
+    completed: HashSet<String>,
+    // Legs left out of the transfers for what is wrong with them (see `pair`).
+    rejected: Vec<Line>,
+}
+
+/// What registering a leg found: the pair it completes, if any, and what is wrong with it, so
+/// that the caller chooses whether to stop or to carry on.
+#[derive(Debug, Default)]
+pub struct Added {
+    pub pair: Option<Pair>,
+    pub problem: Option<anyhow::Error>,
+}
+
+impl Added {
+    fn problem(error: Error) -> Self {
+        Self {
+            pair: None,
+            problem: Some(error.into()),
+        }
+    }
+}
+
+impl Transfers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a leg of a transfer. Once both legs are known, they are returned as a pair
+    /// (source first), along with what prevents them from balancing.
+    pub fn add(&mut self, line: &Line, exchange: &Exchange) -> Added {
+        let id = line.transfer();
+
+        if id.is_empty() {
+            return Added::problem(Error::UnlinkedTransfer {
+                account: line.account(),
+                date: line.date(),
+            });
+        }
+
+        if self.completed.contains(&id) {
+            return Added::problem(Error::TransferLegs { id });
+        }
+
+        match self.pending.remove(&id) {
+            None => {
+                self.pending.insert(id, line.clone());
+
+                Added::default()
+            }
+            Some(other) => {
+                self.completed.insert(id);
+
+                let pair = Pair::new(other, line.clone());
+                let problem = pair.validate(exchange).err();
+
+                Added {
+                    pair: Some(pair),
+                    problem,
+                }
+            }
+        }
+    }
+
+    /// Register a leg of a transfer as `add` does, for the readers of the ledger that carry on
+    /// whatever is wrong (leaving it to `ledger check`): the problem is logged, and the legs
+    /// involved are left unpaired.
+    pub fn pair(&mut self, line: &Line, exchange: &Exchange) -> Option<Pair> {
+        let added = self.add(line, exchange);
+
+        let problem = match added.problem {
+            Some(value) => value,
+            None => return added.pair,
+        };
+
+        log::warn!("{} (left unpaired)", problem);
+
+        match added.pair {
+            Some(pair) => {
+                self.rejected.push(pair.source);
+                self.rejected.push(pair.destination);
+            }
+            None => self.rejected.push(line.clone()),
+        }
+
+        None
+    }
+
+    /// Legs whose counterpart was not found (yet).
+    pub fn pending(&self) -> Vec<&Line> {
+        let mut lines: Vec<&Line> = self.pending.values().collect();
+
+        lines.sort();
+
+        lines
+    }
+
+    /// Legs that are not part of any pair: the pending ones and the ones left out by `pair`.
+    pub fn unpaired(&self) -> Vec<&Line> {
+        let mut lines: Vec<&Line> = self.pending.values().chain(&self.rejected).collect();
+
+        lines.sort();
+
+        lines
+    }
+}
+
+/// Both legs of a transfer.
+#[derive(Debug, Clone)]
+pub struct Pair {
+    pub source: Line,
+    pub destination: Line,
+}
+
+impl Pair {
+    fn new(first: Line, second: Line) -> Self {
+        if second.amount().negative() && !first.amount().negative() {
+            Self {
+                source: second,
+                destination: first,
+            }
+        } else {
+            Self {
+                source: first,
+                destination: second,
+            }
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.source.transfer()
+    }
+
+    /// Check that the amounts of both legs cancel each other out, once in the same currency.
+    pub fn validate(&self, exchange: &Exchange) -> anyhow::Result<()> {
+        let source = self.source.amount();
+        let destination = self.destination.amount();
+
+        let exchanged =
+            destination.exchange(source.currency(), exchange, self.destination.date())?;
+
+        let difference = (source.cents() + exchanged.cents()).abs() as f64;
+
+        let allowed = if source.currency() == destination.currency() {
+            0.0
+        } else {
+            (source.cents().abs() as f64) * TOLERANCE
+        };
+
+        if difference > allowed {
+            return Err(Error::UnbalancedTransfer {
+                id: self.id(),
+                source,
+                destination,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// A new transfer identifier for the date, different from all the existing ones.
+pub fn identifier(date: Date, existing: &HashSet<String>) -> String {
+    let prefix = date.format("%Y%m%d").to_string();
+
+    (1..)
+        .map(|n| format!("{}-{}", prefix, n))
+        .find(|id| !existing.contains(id))
+        .unwrap_or(prefix)
+}
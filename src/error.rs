@@ -0,0 +1,100 @@
+use std::fmt;
+
+use crate::entity::date::Date;
+use crate::entity::money::Money;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors raised by the ledger operations.
+///
+/// Functions returning `anyhow::Result` may also carry one of these, which can be recovered with
+/// `error.downcast_ref::<ledger::Error>()`.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// An operation between amounts in different currencies (e.g. "+", "-").
+    CurrencyMismatch {
+        operation: &'static str,
+        left: Money,
+        right: Money,
+    },
+    /// The result of an operation between amounts (e.g. "+", "-") is too big to be represented.
+    Overflow {
+        operation: &'static str,
+        left: Money,
+        right: Money,
+    },
+    /// There is no exchange rate for the currency (identified by its code).
+    MissingExchangeRate { code: String },
+    /// The quantity of an investment is not a whole number.
+    InvalidQuantity { code: String, value: String },
+    /// The price of an asset could not be obtained.
+    Asset { isin: String, reason: String },
+    /// A transfer leg without an identifier (booked before transfers had one).
+    UnlinkedTransfer { account: String, date: Date },
+    /// More than two legs share the same transfer identifier.
+    TransferLegs { id: String },
+    /// The amounts of both legs of a transfer do not cancel each other out.
+    UnbalancedTransfer {
+        id: String,
+        source: Money,
+        destination: Money,
+    },
+    /// The key derivation of an encrypted file asks for more than the strongest limits.
+    KdfLimits { opslimit: u64, memlimit: u64 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::CurrencyMismatch {
+                operation,
+                left,
+                right,
+            } => write!(
+                f,
+                "{} {} {}: Cannot perform operations between different currencies",
+                left, operation, right
+            ),
+            Error::Overflow {
+                operation,
+                left,
+                right,
+            } => write!(f, "{} {} {}: The result is too big", left, operation, right),
+            Error::MissingExchangeRate { code } => {
+                write!(f, "There is no exchange currency for '{}'", code)
+            }
+            Error::InvalidQuantity { code, value } => write!(
+                f,
+                "The quantity of '{}' must be a whole number (found '{}')",
+                code, value
+            ),
+            Error::Asset { isin, reason } => {
+                write!(f, "The price of '{}' is unavailable: {}", isin, reason)
+            }
+            Error::UnlinkedTransfer { account, date } => write!(
+                f,
+                "The transfer of '{}' on {} has no identifier (run 'ledger link' to assign one)",
+                account, date
+            ),
+            Error::TransferLegs { id } => {
+                write!(f, "The transfer '{}' has more than two legs", id)
+            }
+            Error::UnbalancedTransfer {
+                id,
+                source,
+                destination,
+            } => write!(
+                f,
+                "The legs of the transfer '{}' do not balance ({} and {})",
+                id, source, destination
+            ),
+            Error::KdfLimits { opslimit, memlimit } => write!(
+                f,
+                "The key derivation limits of the file are too high (opslimit {}, memlimit {})",
+                opslimit, memlimit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
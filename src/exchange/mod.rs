@@ -0,0 +1,216 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::money::Currency;
+use crate::error::Error;
+use crate::service::openexchangerates;
+use crate::xdg::Xdg;
+
+pub mod provider;
+
+use provider::Provider;
+
+const EXCHANGE_CACHE_FILENAME: &str = "exchange.yml";
+const EXCHANGE_HISTORY_DIRECTORY: &str = "exchange";
+const EXCHANGE_CACHE_TTL: u64 = 43200; // 12 hours
+const FALLBACK_WINDOW: i64 = 30; // days
+
+/// What to do when there are no rates for the date of a line.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fallback {
+    /// Use the most recent rates available.
+    #[default]
+    Latest,
+    /// Use the closest earlier date with known rates (up to 30 days before).
+    Previous,
+    /// Fail the operation.
+    Fail,
+}
+
+/// A snapshot of the exchange rates, relative to a base currency.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rates {
+    timestamp: i64,
+    base: String,
+    rates: BTreeMap<String, f32>,
+}
+
+impl From<openexchangerates::ExchangeRate> for Rates {
+    fn from(item: openexchangerates::ExchangeRate) -> Self {
+        Rates {
+            timestamp: item.timestamp,
+            base: item.base,
+            rates: item.rates,
+        }
+    }
+}
+
+impl Rates {
+    pub fn rate(&self, from: Currency, to: Currency) -> crate::Result<f32> {
+        match self.rates.get(&to.code()) {
+            None => Err(Error::MissingExchangeRate { code: to.code() }),
+            Some(dividend) => match self.rates.get(&from.code()) {
+                None => Err(Error::MissingExchangeRate { code: from.code() }),
+                Some(divisor) => Ok(dividend / divisor),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    filepath: String,
+}
+
+impl Cache {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            filepath: Xdg::Cache(EXCHANGE_CACHE_FILENAME.to_string()).filepath()?,
+        })
+    }
+
+    /// Cache of the rates on a given day. These never expire, since past rates do not change.
+    pub fn on(date: Date) -> anyhow::Result<Self> {
+        let filename = format!("{}/{}.yml", EXCHANGE_HISTORY_DIRECTORY, date);
+
+        Ok(Self {
+            filepath: Xdg::Cache(filename).filepath()?,
+        })
+    }
+
+    pub fn exists(&self) -> bool {
+        Path::new(&self.filepath).exists()
+    }
+
+    pub fn valid(&self) -> bool {
+        let path = Path::new(&self.filepath);
+
+        let mtime = path
+            .metadata()
+            .and_then(|v| v.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let default = Duration::new(EXCHANGE_CACHE_TTL, 0);
+
+        let interval = SystemTime::now()
+            .duration_since(mtime)
+            .unwrap_or(default)
+            .as_secs();
+
+        path.exists() && interval < EXCHANGE_CACHE_TTL
+    }
+
+    pub fn open(&self) -> anyhow::Result<File> {
+        Ok(File::open(&self.filepath)?)
+    }
+
+    pub fn create(&self) -> anyhow::Result<File> {
+        Ok(File::create(&self.filepath)?)
+    }
+
+    fn load(&self) -> anyhow::Result<Rates> {
+        Ok(serde_yaml::from_reader(self.open()?)?)
+    }
+
+    fn store(&self, rates: Rates) -> anyhow::Result<Rates> {
+        let mut file = self.create()?;
+        let yaml = serde_yaml::to_string(&rates)?;
+        file.write_all(yaml.as_bytes())?;
+        Ok(rates)
+    }
+}
+
+/// Exchange rates over time.
+///
+/// The latest rates are used for anything happening today (or later), while past dates use the
+/// rates of that day, as known by the configured provider. Rates are only requested when needed,
+/// so single-currency ledgers never trigger any download (nor require any provider to be set up).
+#[derive(Debug)]
+pub struct Exchange {
+    provider: Box<dyn Provider>,
+    latest: RefCell<Option<Rates>>,
+    history: RefCell<BTreeMap<Date, Option<Rates>>>,
+    fallback: Fallback,
+}
+
+impl Exchange {
+    pub fn new(config: &Config) -> anyhow::Result<Exchange> {
+        Ok(Exchange {
+            provider: provider::build(config)?,
+            latest: RefCell::new(None),
+            history: RefCell::new(BTreeMap::new()),
+            fallback: config.exchange.fallback,
+        })
+    }
+
+    /// Rate to convert from one currency to the other, as it was on the date provided.
+    pub fn rate(&self, from: Currency, to: Currency, date: Date) -> anyhow::Result<f32> {
+        if date >= Date::today() {
+            return Ok(self.latest()?.rate(from, to)?);
+        }
+
+        match self.historical(date)? {
+            Some(rates) => Ok(rates.rate(from, to)?),
+            None => match self.fallback {
+                Fallback::Latest => Ok(self.latest()?.rate(from, to)?),
+                Fallback::Previous => match self.previous(date)? {
+                    Some(rates) => Ok(rates.rate(from, to)?),
+                    None => Err(anyhow!(
+                        "There are no exchange rates for {} (or the {} days before)",
+                        date,
+                        FALLBACK_WINDOW
+                    )),
+                },
+                Fallback::Fail => Err(anyhow!("There are no exchange rates for {}", date)),
+            },
+        }
+    }
+
+    fn latest(&self) -> anyhow::Result<Rates> {
+        if let Some(rates) = self.latest.borrow().as_ref() {
+            return Ok(rates.clone());
+        }
+
+        let rates = self.provider.latest()?;
+
+        *self.latest.borrow_mut() = Some(rates.clone());
+
+        Ok(rates)
+    }
+
+    fn historical(&self, date: Date) -> anyhow::Result<Option<Rates>> {
+        if let Some(rates) = self.history.borrow().get(&date) {
+            return Ok(rates.clone());
+        }
+
+        let rates = self.provider.historical(date)?;
+
+        self.history.borrow_mut().insert(date, rates.clone());
+
+        Ok(rates)
+    }
+
+    fn previous(&self, date: Date) -> anyhow::Result<Option<Rates>> {
+        let mut available_date = date.pred();
+
+        while date.since(available_date).num_days() <= FALLBACK_WINDOW {
+            if let Some(rates) = self.historical(available_date)? {
+                return Ok(Some(rates));
+            }
+
+            available_date = available_date.pred();
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::exchange::{Cache, Rates};
+use crate::service::{ecb, openexchangerates};
+
+/// Where the exchange rates come from (`exchange.provider` in the configuration).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Source {
+    /// Download the rates from openexchangerates.org (requires `exchange_key`).
+    #[default]
+    OpenExchangeRates,
+    /// Read the rates from a local YAML or CSV file, relative to `base`.
+    Table {
+        path: String,
+        #[serde(default = "default_base")]
+        base: String,
+    },
+    /// Read the rates from a local copy of the ECB daily/historical XML feed.
+    Ecb { path: String },
+}
+
+fn default_base() -> String {
+    ecb::BASE.to_string()
+}
+
+/// A source of exchange rates.
+pub trait Provider: std::fmt::Debug {
+    /// The most recent rates known.
+    fn latest(&self) -> anyhow::Result<Rates>;
+
+    /// The rates at the end of the given day, if known.
+    fn historical(&self, date: Date) -> anyhow::Result<Option<Rates>>;
+}
+
+pub fn build(config: &Config) -> anyhow::Result<Box<dyn Provider>> {
+    match &config.exchange.provider {
+        Source::OpenExchangeRates => Ok(Box::new(OpenExchangeRates::new(config.exchange_key()))),
+        Source::Table { path, base } => Ok(Box::new(Local::table(&expand(path), base)?)),
+        Source::Ecb { path } => Ok(Box::new(Local::ecb(&expand(path))?)),
+    }
+}
+
+fn expand(path: &str) -> String {
+    shellexpand::tilde(path).to_string()
+}
+
+/// Rates downloaded from openexchangerates.org, kept in the cache directory.
+#[derive(Debug)]
+pub struct OpenExchangeRates {
+    key: Option<String>,
+    offline: Cell<bool>,
+}
+
+impl OpenExchangeRates {
+    pub fn new(key: Option<String>) -> Self {
+        Self {
+            key,
+            offline: Cell::new(false),
+        }
+    }
+
+    fn client(&self) -> anyhow::Result<openexchangerates::Client> {
+        let key = self.key.as_ref().ok_or_else(|| {
+            anyhow!("The openexchangerates provider requires 'exchange_key' to be configured")
+        })?;
+
+        Ok(openexchangerates::Client::new(key.to_string()))
+    }
+}
+
+impl Provider for OpenExchangeRates {
+    fn latest(&self) -> anyhow::Result<Rates> {
+        let cache = Cache::new()?;
+
+        if cache.valid() {
+            return cache.load();
+        }
+
+        match self.client().and_then(|client| client.latest()) {
+            Ok(result) => cache.store(result.into()),
+            Err(e) if cache.exists() => {
+                log::warn!("Could not download exchange rates, using cached ones: {}", e);
+                cache.load()
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn historical(&self, date: Date) -> anyhow::Result<Option<Rates>> {
+        let cache = Cache::on(date)?;
+
+        if cache.exists() {
+            return Ok(Some(cache.load()?));
+        }
+
+        if self.offline.get() {
+            return Ok(None);
+        }
+
+        match self.client().and_then(|client| client.historical(date)) {
+            Ok(result) => Ok(Some(cache.store(result.into())?)),
+            Err(e) => {
+                // Avoid retrying (and waiting on) the service for every other missing date.
+                log::warn!("Could not download exchange rates for {}: {}", date, e);
+                self.offline.set(true);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Rates per day read from a local file, for offline usage.
+#[derive(Debug)]
+pub struct Local {
+    days: BTreeMap<Date, Rates>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TableRow {
+    date: Date,
+    currency: String,
+    rate: f32,
+}
+
+impl Local {
+    /// A table of rates relative to `base`, either as CSV (with the columns Date, Currency and
+    /// Rate) or as YAML (a map of dates to a map of currencies to rates).
+    pub fn table(path: &str, base: &str) -> anyhow::Result<Self> {
+        let file = File::open(path).with_context(|| format!("Could not open '{}'", path))?;
+
+        let table: BTreeMap<Date, BTreeMap<String, f32>> =
+            match Path::new(path).extension().and_then(|v| v.to_str()) {
+                Some("csv") => {
+                    let mut table: BTreeMap<Date, BTreeMap<String, f32>> = BTreeMap::new();
+
+                    for result in csv::Reader::from_reader(file).deserialize() {
+                        let row: TableRow = result?;
+
+                        table
+                            .entry(row.date)
+                            .or_default()
+                            .insert(row.currency.to_uppercase(), row.rate);
+                    }
+
+                    table
+                }
+                _ => serde_yaml::from_reader(file)?,
+            };
+
+        Ok(Self::from_table(table, base))
+    }
+
+    /// The euro foreign exchange reference rates, as published by the ECB.
+    pub fn ecb(path: &str) -> anyhow::Result<Self> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Could not open '{}'", path))?;
+
+        Ok(Self::from_table(ecb::parse(&content)?, ecb::BASE))
+    }
+
+    fn from_table(table: BTreeMap<Date, BTreeMap<String, f32>>, base: &str) -> Self {
+        let epoch = Date::from_ymd(1970, 1, 1);
+
+        let days = table
+            .into_iter()
+            .map(|(date, mut rates)| {
+                rates.insert(base.to_uppercase(), 1.0);
+
+                let value = Rates {
+                    timestamp: date.since(epoch).num_seconds(),
+                    base: base.to_uppercase(),
+                    rates,
+                };
+
+                (date, value)
+            })
+            .collect();
+
+        Self { days }
+    }
+}
+
+impl Provider for Local {
+    fn latest(&self) -> anyhow::Result<Rates> {
+        self.days
+            .values()
+            .next_back()
+            .cloned()
+            .ok_or_else(|| anyhow!("There are no exchange rates in the file"))
+    }
+
+    fn historical(&self, date: Date) -> anyhow::Result<Option<Rates>> {
+        Ok(self.days.get(&date).cloned())
+    }
+}
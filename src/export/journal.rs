@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::entity::money::Money;
+use crate::entity::transfer::{Pair, Transfers};
+use crate::exchange::Exchange;
+use crate::resource::Resource;
+use crate::Mode;
+
+// Counterpart of the legs of transfers whose other leg is missing.
+const UNPAIRED_TRANSFERS: &str = "Equity:Transfers";
+
+/// The ledger as a journal of hledger/ledger-cli, for plain text accounting tools.
+///
+/// Each transaction moves the amount between the account (`Assets:<Account>`) and its category
+/// (`Expenses:<Category>` or `Income:<Category>`, by the sign of the amount), while both legs of
+/// a transfer are a single transaction between the two accounts. Currencies are the commodities
+/// and venues, trips and transfer identifiers are tags.
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Vec<(Date, String)>,
+}
+
+impl Journal {
+    pub fn new(config: &Config, exchange: &Exchange) -> anyhow::Result<Journal> {
+        let mut journal = Self::default();
+
+        let mut transfers = Transfers::new();
+
+        let mut resource = Resource::new(config, Mode::Ledger)?;
+
+        resource.line(&mut |record| {
+            if record.category() == config.transfer {
+                if let Some(pair) = transfers.pair(record, exchange) {
+                    journal.transfer(&pair);
+                }
+            } else {
+                journal.transaction(record, &category(record));
+            }
+
+            Ok(())
+        })?;
+
+        for line in transfers.unpaired() {
+            journal.transaction(line, UNPAIRED_TRANSFERS);
+        }
+
+        // Transfers are only known once both legs are read.
+        journal.entries.sort_by_key(|(date, _)| *date);
+
+        Ok(journal)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut file = File::create(path)?;
+
+        for (_, entry) in self.entries.iter() {
+            writeln!(file, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+
+    fn transaction(&mut self, line: &Line, counterpart: &str) {
+        let amount = line.amount();
+
+        let mut entry = header(line, &tags(line, &[]));
+
+        let balance = Money::new(amount.currency(), -amount.cents());
+
+        entry.push_str(&posting(counterpart, &format(&balance)));
+        entry.push_str(&posting(&asset(&line.account()), &format(&amount)));
+
+        self.entries.push((line.date(), entry));
+    }
+
+    // Legs in different currencies are balanced with the total price of the amount received.
+    fn transfer(&mut self, pair: &Pair) {
+        let source = pair.source.amount();
+        let destination = pair.destination.amount();
+
+        let received = if source.currency() == destination.currency() {
+            format(&destination)
+        } else {
+            format!("{} @@ {}", format(&destination), format(&source.abs()))
+        };
+
+        let tags = tags(&pair.source, &[("transfer", pair.id())]);
+
+        let mut entry = header(&pair.source, &tags);
+
+        entry.push_str(&posting(&asset(&pair.destination.account()), &received));
+        entry.push_str(&posting(&asset(&pair.source.account()), &format(&source)));
+
+        self.entries.push((pair.source.date(), entry));
+    }
+}
+
+fn header(line: &Line, tags: &[String]) -> String {
+    let description: Vec<String> = vec![line.venue(), line.description()]
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .collect();
+
+    let mut header = format!("{} {}", line.date(), description.join(" | "));
+
+    if !tags.is_empty() {
+        header.push_str(&format!("  ; {}", tags.join(", ")));
+    }
+
+    format!("{}\n", header.trim_end())
+}
+
+fn posting(account: &str, amount: &str) -> String {
+    format!("    {:<40}  {}\n", account, amount)
+}
+
+fn tags(line: &Line, extra: &[(&str, String)]) -> Vec<String> {
+    let values = [
+        ("venue", line.venue()),
+        ("trip", line.trip()),
+        ("quantity", line.quantity()),
+    ];
+
+    values
+        .iter()
+        .chain(extra.iter())
+        .filter(|(_, value)| !value.is_empty())
+        // Commas separate the tags, so they cannot be part of the values.
+        .map(|(name, value)| format!("{}:{}", name, value.replace(',', " ")))
+        .collect()
+}
+
+fn category(line: &Line) -> String {
+    if line.amount().negative() {
+        format!("Expenses:{}", name(&line.category()))
+    } else {
+        format!("Income:{}", name(&line.category()))
+    }
+}
+
+fn asset(account: &str) -> String {
+    format!("Assets:{}", name(account))
+}
+
+// Two spaces end the name of an account, so any sequence of whitespace is replaced by one.
+fn name(value: &str) -> String {
+    match value.split_whitespace().collect::<Vec<&str>>().join(" ") {
+        name if name.is_empty() => "Unknown".to_string(),
+        name => name,
+    }
+}
+
+fn format(money: &Money) -> String {
+    let precision = money.currency().decimal_places() as usize;
+
+    format!(
+        "{:.precision$} {}",
+        money.to_number(),
+        money.currency().code(),
+        precision = precision
+    )
+}
@@ -0,0 +1,121 @@
+//! Writing the ledger in the formats of other tools.
+
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::hash::sha256;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::entity::line::{Line, Liner};
+use crate::xdg::Xdg;
+use crate::Mode;
+
+pub mod journal;
+pub mod profile;
+
+const EXPORTED_STATE_FILENAME: &str = "exported.yml";
+
+/// Settings of `ledger export` (`export` in the configuration).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Settings {
+    /// Formats of the CSV files exported, by name (e.g. one per application).
+    #[serde(default)]
+    pub profiles: BTreeMap<String, profile::Profile>,
+}
+
+/// Lines already exported with each profile of the configuration, kept in the cache directory, so
+/// that the profiles do not hide lines from each other (the `Exported` column of the files is
+/// only used without a profile).
+///
+/// Lines are known by a digest of their values, which keeps the cache free of the content of
+/// encrypted files.
+#[derive(Debug)]
+pub struct Exported {
+    filepath: String,
+    profile: String,
+    pub keys: BTreeMap<String, BTreeSet<String>>,
+    // Occurrences of each line so far, to tell apart lines with the same values.
+    seen: HashMap<String, usize>,
+}
+
+impl Exported {
+    pub fn load(profile: &str) -> anyhow::Result<Exported> {
+        let filepath = Xdg::Cache(EXPORTED_STATE_FILENAME.to_string()).filepath()?;
+
+        let keys = if Path::new(&filepath).exists() {
+            serde_yaml::from_reader(File::open(&filepath)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            filepath,
+            profile: profile.to_string(),
+            keys,
+            seen: HashMap::new(),
+        })
+    }
+
+    /// Key of the next line of the file, read in order (the `Exported` column is left out).
+    pub fn key(&mut self, mode: Mode, line: &Line) -> String {
+        let values = format!(
+            "{:?}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            mode,
+            line.date(),
+            line.account(),
+            line.category(),
+            line.description(),
+            line.quantity(),
+            line.venue(),
+            line.amount(),
+            line.currency().code(),
+            line.trip(),
+            line.transfer(),
+            line.investment(),
+        );
+
+        let occurrence = self.seen.entry(values.to_string()).or_default();
+        *occurrence += 1;
+
+        let digest = sha256::hash(format!("{}#{}", values, occurrence).as_bytes());
+
+        digest
+            .0
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Key of a transfer, which is exported once for both legs.
+    pub fn transfer_key(id: &str) -> String {
+        format!("transfer:{}", id)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys
+            .get(&self.profile)
+            .is_some_and(|keys| keys.contains(key))
+    }
+
+    pub fn insert(&mut self, key: String) {
+        self.keys
+            .entry(self.profile.to_string())
+            .or_default()
+            .insert(key);
+    }
+
+    pub fn store(&self) -> anyhow::Result<()> {
+        let directory = Path::new(&self.filepath)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        let yaml = serde_yaml::to_string(&self.keys)?;
+        file.write_all(yaml.as_bytes())?;
+        file.persist(&self.filepath).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
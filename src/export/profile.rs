@@ -0,0 +1,247 @@
+use anyhow::anyhow;
+use chrono::format::{Item, StrftimeItems};
+use serde::{Deserialize, Serialize};
+
+use crate::entity::date::Date;
+use crate::entity::money::{Currency, Money};
+use crate::exchange::Exchange;
+
+/// Values of a line that can be used in the columns (as `{name}`).
+pub const FIELDS: [&str; 13] = [
+    "account",
+    "date",
+    "category",
+    "description",
+    "quantity",
+    "venue",
+    "amount",
+    "currency",
+    "trip",
+    "transfer",
+    "payee",
+    "notes",
+    "exported",
+];
+
+/// Currency of the amounts exported.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    /// Exchanged to the default currency (with the rates of the date of each line).
+    #[default]
+    Converted,
+    /// In the currency of each line.
+    Original,
+}
+
+/// A column of the CSV files: the header and its value, with the fields of the line between
+/// braces (e.g. `{description} ({venue})`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Column {
+    pub header: String,
+    pub value: String,
+}
+
+/// Format of the CSV files exported.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default = "Profile::default_columns")]
+    pub columns: Vec<Column>,
+    #[serde(default = "Profile::default_date_format")]
+    pub date_format: String,
+    #[serde(default)]
+    pub currency: Policy,
+    /// Name of the files, with `{account}` replaced by the account (a single file without it).
+    #[serde(default = "Profile::default_file")]
+    pub file: String,
+    /// Whether the lines exported are remembered for this profile (and not exported again).
+    #[serde(default = "Profile::default_mark")]
+    pub mark: bool,
+    /// Whether the entries of the networth are exported too (as the change of the investments).
+    #[serde(default = "Profile::default_networth")]
+    pub networth: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            columns: Self::default_columns(),
+            date_format: Self::default_date_format(),
+            currency: Policy::default(),
+            file: Self::default_file(),
+            mark: Self::default_mark(),
+            networth: Self::default_networth(),
+        }
+    }
+}
+
+/// Values of a line to export.
+#[derive(Debug, Default)]
+pub struct Fields {
+    pub account: String,
+    pub date: Date,
+    pub category: String,
+    pub description: String,
+    pub quantity: String,
+    pub venue: String,
+    pub amount: Money,
+    pub trip: String,
+    pub transfer: String,
+    /// Counterpart of the line (the venue, or the destination for transfers).
+    pub payee: String,
+    /// Description with the quantity, if any.
+    pub notes: String,
+    pub exported: String,
+}
+
+impl Profile {
+    fn default_columns() -> Vec<Column> {
+        [
+            ("Date", "{date}"),
+            ("Payee", "{payee}"),
+            ("Notes", "{notes}"),
+            ("Category", "{category}"),
+            ("Amount", "{amount}"),
+        ]
+        .iter()
+        .map(|(header, value)| Column {
+            header: header.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+    }
+
+    fn default_date_format() -> String {
+        "%Y-%m-%d".to_string()
+    }
+
+    fn default_file() -> String {
+        "{account}.csv".to_string()
+    }
+
+    fn default_mark() -> bool {
+        true
+    }
+
+    fn default_networth() -> bool {
+        true
+    }
+
+    /// Check that the columns only use known fields and that the date format is valid.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error)) {
+            return Err(anyhow!("Invalid date format '{}'", self.date_format));
+        }
+
+        for column in self.columns.iter() {
+            for name in placeholders(&column.value) {
+                if !FIELDS.contains(&name) {
+                    return Err(anyhow!(
+                        "Unknown field '{}' on column '{}' (expected one of: {})",
+                        name,
+                        column.header,
+                        FIELDS.join(", ")
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn headers(&self) -> Vec<String> {
+        self.columns.iter().map(|v| v.header.to_string()).collect()
+    }
+
+    /// Name of the file of the account.
+    pub fn filename(&self, account: &str) -> String {
+        let account: String = account
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        self.file.replace("{account}", &account)
+    }
+
+    pub fn row(
+        &self,
+        fields: &Fields,
+        currency: Currency,
+        exchange: &Exchange,
+    ) -> anyhow::Result<Vec<String>> {
+        let amount = match self.currency {
+            Policy::Converted => fields.amount.exchange(currency, exchange, fields.date)?,
+            Policy::Original => fields.amount,
+        };
+
+        let precision = amount.currency().decimal_places() as usize;
+
+        let value = |name: &str| -> String {
+            match name {
+                "account" => fields.account.to_string(),
+                "date" => fields.date.format(&self.date_format).to_string(),
+                "category" => fields.category.to_string(),
+                "description" => fields.description.to_string(),
+                "quantity" => fields.quantity.to_string(),
+                "venue" => fields.venue.to_string(),
+                "amount" => format!("{:.precision$}", amount.to_number(), precision = precision),
+                "currency" => amount.currency().code(),
+                "trip" => fields.trip.to_string(),
+                "transfer" => fields.transfer.to_string(),
+                "payee" => fields.payee.to_string(),
+                "notes" => fields.notes.to_string(),
+                "exported" => fields.exported.to_string(),
+                _ => String::new(),
+            }
+        };
+
+        Ok(self
+            .columns
+            .iter()
+            .map(|column| render(&column.value, &value))
+            .collect())
+    }
+}
+
+fn placeholders(template: &str) -> Vec<&str> {
+    parts(template)
+        .into_iter()
+        .filter_map(|(text, field)| if field { Some(text) } else { None })
+        .collect()
+}
+
+fn render(template: &str, value: &dyn Fn(&str) -> String) -> String {
+    parts(template)
+        .into_iter()
+        .map(|(text, field)| if field { value(text) } else { text.to_string() })
+        .collect()
+}
+
+// The template split in text and fields (`true` for the names between braces).
+fn parts(template: &str) -> Vec<(&str, bool)> {
+    let mut result = Vec::new();
+
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(index) => start + index,
+            None => break,
+        };
+
+        result.push((&rest[..start], false));
+        result.push((&rest[start + 1..end], true));
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push((rest, false));
+
+    result
+}
@@ -1,3 +1,5 @@
+use clap::Args;
+
 use std::ops::RangeInclusive;
 
 use crate::cmd::{balance, report, show};
@@ -5,6 +7,23 @@ use crate::config::Config;
 use crate::entity::date::Date;
 use crate::entity::line::{Line, Liner};
 
+/// Period of the reports (the current month by default), shared by the commands.
+#[derive(Args, Clone, Debug)]
+pub struct Period {
+    /// Select entries that occurred on the year
+    #[arg(short, long)]
+    pub year: Option<i32>,
+    /// Select entries that occurred on the month
+    #[arg(short, long)]
+    pub month: Option<u32>,
+    /// Select entries that occurred after the date
+    #[arg(short, long)]
+    pub from: Option<Date>,
+    /// Select entries that occurred before the date
+    #[arg(short, long)]
+    pub till: Option<Date>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Filter {
     pub start: Option<Date>,
@@ -39,19 +58,37 @@ impl Filter {
     }
 
     pub fn report(args: &report::Args, config: &Config) -> Self {
+        Self {
+            excluded_categories: args.exclude.clone(),
+            ..Self::for_period(&args.period, config)
+        }
+    }
+
+    pub fn comparison(args: &report::Args, config: &Config, start: Date, end: Date) -> Self {
+        Self {
+            excluded_categories: args.exclude.clone(),
+            ..Self::between(config, Some(start), Some(end))
+        }
+    }
+
+    pub fn for_period(period: &Period, config: &Config) -> Self {
         let today = Date::today();
 
         let (start, end) = Self::bounds(
-            Some(args.year.unwrap_or_else(|| today.year())),
-            Some(args.month.unwrap_or_else(|| today.month())),
-            args.from,
-            args.till,
+            Some(period.year.unwrap_or_else(|| today.year())),
+            Some(period.month.unwrap_or_else(|| today.month())),
+            period.from,
+            period.till,
         );
 
+        Self::between(config, start, end)
+    }
+
+    /// Filter used by reports over the ledger for the given (inclusive) period.
+    pub fn between(config: &Config, start: Option<Date>, end: Option<Date>) -> Self {
         Self {
             start,
             end,
-            excluded_categories: args.exclude.clone(),
             transfer: config.transfer.clone(),
             ignored_accounts: config.ignored_accounts.clone(),
             investments: config.investments.clone(),
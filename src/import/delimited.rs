@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+use crate::config::Config;
+use crate::import::Record;
+
+/// How the amounts of a statement are signed.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sign {
+    /// Expenses are negative.
+    #[default]
+    Normal,
+    /// Expenses are positive (e.g. statements of credit cards).
+    Inverted,
+}
+
+/// Format of the CSV statements of a bank.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    /// Account of the transactions imported.
+    pub account: String,
+    /// Currency of the transactions, when there is no column for it (the default one otherwise).
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default = "Profile::default_delimiter")]
+    pub delimiter: char,
+    /// Number of lines before the headers.
+    #[serde(default)]
+    pub skip: usize,
+    #[serde(default = "Profile::default_date_format")]
+    pub date_format: String,
+    /// Separator of the decimal places (the other one of `.` and `,` is ignored).
+    #[serde(default = "Profile::default_decimal")]
+    pub decimal: char,
+    #[serde(default)]
+    pub sign: Sign,
+    /// Category of the transactions not matched by any rule (`import.category` by default).
+    #[serde(default)]
+    pub category: Option<String>,
+    pub columns: Columns,
+}
+
+/// Headers of the columns of the statement.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Columns {
+    pub date: String,
+    pub description: String,
+    /// Signed amount (unless there are separate columns for debits and credits).
+    #[serde(default)]
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub debit: Option<String>,
+    #[serde(default)]
+    pub credit: Option<String>,
+    #[serde(default)]
+    pub venue: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl Profile {
+    fn default_delimiter() -> char {
+        ','
+    }
+
+    fn default_date_format() -> String {
+        "%Y-%m-%d".to_string()
+    }
+
+    fn default_decimal() -> char {
+        '.'
+    }
+
+    /// Read the transactions of the statement.
+    pub fn read(&self, path: &str, config: &Config) -> anyhow::Result<Vec<Record>> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Could not read '{}'", path))?;
+
+        let content: String = content
+            .lines()
+            .skip(self.skip)
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter as u8)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+
+        let headers = rdr.headers()?.clone();
+
+        let index = |name: &Option<String>| -> anyhow::Result<Option<usize>> {
+            match name {
+                None => Ok(None),
+                Some(name) => match headers.iter().position(|header| header == name) {
+                    Some(index) => Ok(Some(index)),
+                    None => Err(anyhow!("There is no column '{}' in '{}'", name, path)),
+                },
+            }
+        };
+
+        let date = index(&Some(self.columns.date.to_string()))?.unwrap_or_default();
+        let description = index(&Some(self.columns.description.to_string()))?.unwrap_or_default();
+        let amount = index(&self.columns.amount)?;
+        let debit = index(&self.columns.debit)?;
+        let credit = index(&self.columns.credit)?;
+        let venue = index(&self.columns.venue)?;
+        let currency = index(&self.columns.currency)?;
+
+        if amount.is_none() && debit.is_none() && credit.is_none() {
+            return Err(anyhow!(
+                "The columns of the amount (either 'amount' or 'debit' and 'credit') are missing"
+            ));
+        }
+
+        let default_currency = self
+            .currency
+            .clone()
+            .unwrap_or_else(|| config.currency.to_string());
+
+        let mut records = Vec::new();
+
+        for (number, result) in rdr.records().enumerate() {
+            let row = result?;
+
+            if row.iter().all(|value| value.is_empty()) {
+                continue;
+            }
+
+            // Headers are the first line after the ones skipped.
+            let line = (self.skip + number + 2) as u64;
+
+            let value = |index: Option<usize>| -> String {
+                index
+                    .and_then(|index| row.get(index))
+                    .unwrap_or_default()
+                    .to_string()
+            };
+
+            let parsed = NaiveDate::parse_from_str(&value(Some(date)), &self.date_format)
+                .with_context(|| {
+                    format!(
+                        "Line {}: invalid date '{}' (expected {})",
+                        line,
+                        value(Some(date)),
+                        self.date_format
+                    )
+                })?;
+
+            let mut total = match amount {
+                Some(_) => self.number(&value(amount), line)?,
+                None => {
+                    let credit = self.number(&value(credit), line)?;
+                    let debit = self.number(&value(debit), line)?;
+
+                    credit.abs() - debit.abs()
+                }
+            };
+
+            if let Sign::Inverted = self.sign {
+                total = -total;
+            }
+
+            let currency = match value(currency) {
+                code if code.is_empty() => default_currency.to_string(),
+                code => code,
+            };
+
+            records.push(Record {
+                line,
+                id: None,
+                account: self.account.to_string(),
+                date: parsed.into(),
+                category: self
+                    .category
+                    .clone()
+                    .unwrap_or_else(|| config.import.category.to_string()),
+                description: value(Some(description)),
+                venue: value(venue),
+                amount: total.to_string(),
+                currency,
+            });
+        }
+
+        Ok(records)
+    }
+
+    // Anything other than digits, signs and the decimal separator (e.g. thousands separators or
+    // currency symbols) is ignored.
+    fn number(&self, value: &str, line: u64) -> anyhow::Result<f64> {
+        let normalized: String = value
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '-' || *c == '+' || *c == self.decimal)
+            .map(|c| if c == self.decimal { '.' } else { c })
+            .collect();
+
+        if normalized.is_empty() {
+            return Ok(0.0);
+        }
+
+        normalized
+            .parse::<f64>()
+            .map_err(|_| anyhow!("Line {}: invalid amount '{}'", line, value))
+    }
+}
@@ -0,0 +1,244 @@
+//! Reading bank statements into transactions.
+
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::entity::date::Date;
+use crate::entity::line::{Line, Liner};
+use crate::xdg::Xdg;
+use crate::Mode;
+
+pub mod delimited;
+pub mod ofx;
+
+const IMPORTED_STATE_FILENAME: &str = "imported.yml";
+
+/// Settings of `ledger import` (`import` in the configuration).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Settings {
+    /// Formats of the CSV statements, by name (e.g. one per bank).
+    #[serde(default)]
+    pub profiles: BTreeMap<String, delimited::Profile>,
+    /// Accounts of the ledger of the accounts in OFX statements (by `ACCTID`).
+    #[serde(default)]
+    pub accounts: BTreeMap<String, String>,
+    /// Applied to every transaction imported, in order (only the first matching is used).
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// Category of the transactions not matched by any rule.
+    #[serde(default = "Settings::default_category")]
+    pub category: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            profiles: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+            rules: vec![],
+            category: Self::default_category(),
+        }
+    }
+}
+
+impl Settings {
+    fn default_category() -> String {
+        "Uncategorized".to_string()
+    }
+}
+
+/// Values set on the transactions whose description matches the pattern (a regular expression).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rule {
+    pub pattern: String,
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub venue: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A transaction read from a statement, before the rules are applied.
+#[derive(Clone, Debug)]
+pub struct Record {
+    /// Line of the statement, for the errors.
+    pub line: u64,
+    /// Identifier given by the bank (e.g. `FITID` of OFX statements).
+    pub id: Option<String>,
+    pub account: String,
+    pub date: Date,
+    pub category: String,
+    pub description: String,
+    pub venue: String,
+    /// Decimal value, with a dot as separator (e.g. -10.5).
+    pub amount: String,
+    pub currency: String,
+}
+
+/// The rules of the configuration, with the patterns compiled.
+#[derive(Debug)]
+pub struct Rules {
+    values: Vec<(Regex, Rule)>,
+}
+
+impl Rules {
+    pub fn new(rules: &[Rule]) -> anyhow::Result<Rules> {
+        let mut values = Vec::new();
+
+        for rule in rules.iter() {
+            let regex = Regex::new(&rule.pattern)
+                .with_context(|| format!("Invalid pattern on import rule '{}'", rule.pattern))?;
+
+            values.push((regex, rule.clone()));
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Build the transaction of the record, with the values of the first rule matching its
+    /// description.
+    pub fn apply(&self, mut record: Record) -> anyhow::Result<Line> {
+        if let Some((_, rule)) = self
+            .values
+            .iter()
+            .find(|(regex, _)| regex.is_match(&record.description))
+        {
+            if let Some(value) = &rule.account {
+                record.account = value.to_string();
+            }
+            if let Some(value) = &rule.category {
+                record.category = value.to_string();
+            }
+            if let Some(value) = &rule.venue {
+                record.venue = value.to_string();
+            }
+            if let Some(value) = &rule.description {
+                record.description = value.to_string();
+            }
+        }
+
+        let number = record.line;
+
+        let values = vec![
+            record.account,
+            record.date.to_string(),
+            record.category,
+            record.description,
+            String::new(),
+            record.venue,
+            record.amount,
+            record.currency,
+            String::new(),
+            String::new(),
+            String::new(),
+        ];
+
+        Line::build(values, Mode::Ledger).with_context(|| format!("Line {}", number))
+    }
+}
+
+/// Transactions already in the ledger, to skip the ones imported before (or booked by hand).
+///
+/// Transactions are the same when they have the same account, date and amount, and each one in
+/// the ledger only matches a single one imported (so that, for example, two coffees on the same
+/// day are both imported if only one was booked).
+#[derive(Debug, Default)]
+pub struct Duplicates {
+    existing: HashMap<String, usize>,
+}
+
+impl Duplicates {
+    pub fn new(lines: &[Line]) -> Self {
+        let mut existing: HashMap<String, usize> = HashMap::new();
+
+        for line in lines.iter() {
+            *existing.entry(Duplicates::key(line)).or_default() += 1;
+        }
+
+        Self { existing }
+    }
+
+    /// Whether the line is already in the ledger (which then no longer matches other lines).
+    pub fn take(&mut self, line: &Line) -> bool {
+        match self.existing.get_mut(&Duplicates::key(line)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn key(line: &Line) -> String {
+        format!(
+            "{}|{}|{}",
+            line.account().to_uppercase(),
+            line.date(),
+            line.amount().to_storage()
+        )
+    }
+}
+
+/// Identifiers of the transactions already imported, per account, kept in the cache directory.
+#[derive(Debug)]
+pub struct Imported {
+    filepath: String,
+    pub ids: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Imported {
+    pub fn load() -> anyhow::Result<Imported> {
+        let filepath = Xdg::Cache(IMPORTED_STATE_FILENAME.to_string()).filepath()?;
+
+        let ids = if Path::new(&filepath).exists() {
+            serde_yaml::from_reader(File::open(&filepath)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { filepath, ids })
+    }
+
+    pub fn contains(&self, record: &Record) -> bool {
+        match &record.id {
+            Some(id) => self
+                .ids
+                .get(&record.account)
+                .is_some_and(|ids| ids.contains(id)),
+            None => false,
+        }
+    }
+
+    pub fn insert(&mut self, record: &Record) {
+        if let Some(id) = &record.id {
+            self.ids
+                .entry(record.account.to_string())
+                .or_default()
+                .insert(id.to_string());
+        }
+    }
+
+    // Written next to the cache and moved over it, so that an interrupted write does not lose
+    // the transactions already imported.
+    pub fn store(&self) -> anyhow::Result<()> {
+        let directory = Path::new(&self.filepath)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        let yaml = serde_yaml::to_string(&self.ids)?;
+        file.write_all(yaml.as_bytes())?;
+        file.persist(&self.filepath).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Context};
+use chrono::NaiveDate;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::import::Record;
+
+/// Whether the file is an OFX (or QFX, the same format) statement, by its extension.
+pub fn matches(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map(|extension| {
+            let extension = extension.to_string_lossy().to_lowercase();
+            extension == "ofx" || extension == "qfx"
+        })
+        .unwrap_or(false)
+}
+
+/// Read the transactions (`STMTTRN`) of every statement of the file, of banks or credit cards.
+///
+/// Both variants of the format are supported: SGML (version 1), where elements are not closed,
+/// and XML (version 2). The account of each statement (`ACCTID`) must be mapped to an account of
+/// the ledger in `import.accounts`.
+pub fn read(path: &str, config: &Config) -> anyhow::Result<Vec<Record>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Could not read '{}'", path))?;
+
+    let mut records = Vec::new();
+
+    let mut account: Option<String> = None;
+    let mut currency = config.currency.to_string();
+    let mut current: Option<(u64, HashMap<String, String>)> = None;
+
+    for token in tokens(&content) {
+        match token {
+            // Each statement has its own account and currency, which must not carry over to the
+            // next one.
+            Token::Open(tag, _) if tag == "STMTRS" || tag == "CCSTMTRS" => {
+                account = None;
+                currency = config.currency.to_string();
+            }
+            Token::Open(tag, line) if tag == "STMTTRN" => current = Some((line, HashMap::new())),
+            Token::Close(tag) if tag == "STMTTRN" => {
+                if let Some((line, fields)) = current.take() {
+                    let id = account
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("Line {}: transaction without an account", line))?;
+
+                    records.push(record(line, id, &currency, &fields, config)?);
+                }
+            }
+            Token::Element(tag, value) => match &mut current {
+                Some((_, fields)) => {
+                    fields.insert(tag, value);
+                }
+                None if tag == "ACCTID" => account = Some(value),
+                None if tag == "CURDEF" => currency = value,
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(records)
+}
+
+fn record(
+    line: u64,
+    id: &str,
+    currency: &str,
+    fields: &HashMap<String, String>,
+    config: &Config,
+) -> anyhow::Result<Record> {
+    let field = |name: &str| fields.get(name).cloned().unwrap_or_default();
+
+    let account = config.import.accounts.get(id).ok_or_else(|| {
+        anyhow!(
+            "The account '{}' is not mapped to any account of the ledger (import.accounts)",
+            id
+        )
+    })?;
+
+    // Dates are YYYYMMDD, optionally followed by the time and the timezone.
+    let posted = field("DTPOSTED");
+    let date = NaiveDate::parse_from_str(posted.get(..8).unwrap_or_default(), "%Y%m%d")
+        .map_err(|_| anyhow!("Line {}: invalid date '{}'", line, posted))?;
+
+    let amount = field("TRNAMT").replace(',', ".");
+    amount
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Line {}: invalid amount '{}'", line, amount))?;
+
+    let description: Vec<String> = vec![field("NAME"), field("MEMO")]
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .collect();
+
+    Ok(Record {
+        line,
+        id: fields.get("FITID").cloned(),
+        account: account.to_string(),
+        date: date.into(),
+        category: config.import.category.to_string(),
+        description: description.join(" "),
+        venue: String::new(),
+        amount,
+        currency: currency.to_string(),
+    })
+}
+
+#[derive(Debug)]
+enum Token {
+    /// A tag opening an aggregate (with the line where it is).
+    Open(String, u64),
+    Close(String),
+    /// A tag with a value (in SGML, the closing tag is optional).
+    Element(String, String),
+}
+
+// Headers (SGML) and processing instructions (XML) are ignored, as well as closing tags of
+// elements (XML), since the value is the text right after the opening tag.
+fn tokens(content: &str) -> Vec<Token> {
+    let mut result = Vec::new();
+
+    let mut rest = content;
+    let mut line = 1;
+
+    while let Some(start) = rest.find('<') {
+        line += rest[..start].matches('\n').count() as u64;
+
+        let end = match rest[start..].find('>') {
+            Some(index) => start + index,
+            None => break,
+        };
+
+        let tag = rest[start + 1..end].trim().to_uppercase();
+
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            result.push(Token::Close(name.to_string()));
+            continue;
+        }
+
+        let text = &rest[..rest.find('<').unwrap_or(rest.len())];
+
+        let value = decode(text.trim());
+
+        if value.is_empty() {
+            result.push(Token::Open(tag, line));
+        } else {
+            result.push(Token::Element(tag, value));
+        }
+    }
+
+    result
+}
+
+fn decode(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
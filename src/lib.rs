@@ -0,0 +1,86 @@
+//! A command line ledger, usable as a library.
+//!
+//! The `ledger` binary is a thin client over this crate, so everything it does
+//! (loading and decrypting the ledger/networth files, exchanging currencies,
+//! building reports) is also available to other tools.
+//!
+//! ```no_run
+//! use ledger::config::Config;
+//! use ledger::entity::line::Liner;
+//! use ledger::entity::report::general;
+//! use ledger::entity::total::Total;
+//! use ledger::exchange::Exchange;
+//! use ledger::filter::Filter;
+//! use ledger::resource::Resource;
+//! use ledger::Mode;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let config = Config::new()?;
+//! let exchange = Exchange::new(&config)?;
+//!
+//! for line in Resource::new(&config, Mode::Ledger)?.lines()? {
+//!     println!("{} {} {}", line.date(), line.account(), line.amount());
+//! }
+//!
+//! let filter = Filter::between(&config, None, None);
+//! let currency = ledger::util::currency(None, &config)?;
+//! let mut total = Total::new(None, &config, filter.end)?;
+//! let report = general::Report::new(currency, &mut total, &config, &exchange, &filter)?;
+//!
+//! for item in report.items() {
+//!     println!("{}: {}", item.category(), item.value());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use clap::ValueEnum;
+
+pub mod backup;
+pub mod cmd;
+pub mod config;
+mod crypto;
+pub mod entity;
+mod error;
+pub mod exchange;
+pub mod export;
+pub mod filter;
+pub mod import;
+pub mod output;
+mod password;
+pub mod price;
+pub mod resource;
+pub mod service;
+pub mod util;
+mod xdg;
+
+pub use error::{Error, Result};
+
+#[macro_export]
+macro_rules! wout {
+    ($($arg:tt)*) => ({
+        use std::io::Write;
+
+        (writeln!(&mut ::std::io::stdout(), $($arg)*)).unwrap();
+    });
+}
+
+#[macro_export]
+macro_rules! werr {
+    ($signal:tt, $($arg:tt)*) => ({
+        use std::io::Write;
+        use std::process;
+
+        (writeln!(&mut ::std::io::stderr(), $($arg)*)).unwrap();
+        process::exit($signal);
+    });
+}
+
+/// Which of the two files a command operates on.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Mode {
+    /// The ledger file, made of `Transaction`s.
+    Ledger,
+    /// The networth file, made of `Entry`s.
+    Networth,
+}
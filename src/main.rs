@@ -1,47 +1,25 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Parser, Subcommand};
 
 use std::process;
 
-mod cmd;
-mod config;
-mod crypto;
-mod entity;
-mod exchange;
-mod filter;
-mod resource;
-mod service;
-mod util;
-mod xdg;
-
-#[macro_export]
-macro_rules! wout {
-    ($($arg:tt)*) => ({
-        use std::io::Write;
-
-        (writeln!(&mut ::std::io::stdout(), $($arg)*)).unwrap();
-    });
-}
-
-#[macro_export]
-macro_rules! werr {
-    ($signal:tt, $($arg:tt)*) => ({
-        use std::io::Write;
-        use std::process;
-
-        (writeln!(&mut ::std::io::stderr(), $($arg)*)).unwrap();
-        process::exit($signal);
-    });
-}
+use ledger::output::Format;
+use ledger::{cmd, werr};
 
 #[derive(Parser, Debug)]
 #[command(author, about, version)]
 pub struct App {
+    /// Format of the reports (balance, budget, check, networth and report)
+    #[arg(long, value_enum, global = true, default_value_t)]
+    pub format: Format,
     #[clap(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Keep the password asked for, so that it is not asked again for a while
+    #[command(hide = true)]
+    Agent(cmd::agent::Args),
     /// Calculate the current balances for each account
     ///
     /// This command will calculate the current balance of each account and
@@ -55,6 +33,24 @@ pub enum Commands {
     /// store the transaction in the ledger file (or the entry in the
     /// networth file).
     Book(cmd::book::Args),
+    /// Compare the budgets with the amount spent per category
+    ///
+    /// This command will display, for each budget defined in the
+    /// configuration, the amount planned and the amount spent on its
+    /// category in the time period (by default, the current month), with what
+    /// is left and the share of the budget already consumed. Budgets are
+    /// scaled to the number of months of the time period.
+    Budget(cmd::budget::Args),
+    /// Check the ledger and networth files for problems
+    ///
+    /// This command will go through both files and report, with the number
+    /// of the line, rows that cannot be read, duplicates, transfers without
+    /// a counterpart (or that do not balance), future dates, dates out of
+    /// order, accounts with more than one currency, investments with a
+    /// quantity that is not a whole number and categories not listed in the
+    /// configuration (if any is). It exits with an error when there is any
+    /// problem, so that it can be used in scripts.
+    Check(cmd::check::Args),
 
     /// Copy default configuration file to the default location
     ///
@@ -80,6 +76,12 @@ pub enum Commands {
     /// already exists, it won't be touched. The file will be created with the
     /// headers, and if encryption is set, it will also be encrypted.
     Create(cmd::create::Args),
+    /// Store the encrypted files in plain text
+    ///
+    /// This command will decrypt the ledger and networth files with the
+    /// password of the configuration and store them in plain text, which
+    /// requires the password to be removed from the configuration afterwards.
+    Decrypt(cmd::decrypt::Args),
     /// Open ledger/networth file in your editor
     ///
     /// Sometimes the best way to do any changes to the CSV is by opening the
@@ -87,21 +89,68 @@ pub enum Commands {
     /// This command does just that, while handling the decryption/encryption
     /// (if enabled).
     Edit(cmd::edit::Args),
+    /// Encrypt the files stored in plain text
+    ///
+    /// This command will encrypt the ledger and networth files that are still
+    /// in plain text with the password of the configuration. Once encryption
+    /// is set, files in plain text are not read, so that encrypted data is
+    /// never mistaken for plain text.
+    Encrypt(cmd::encrypt::Args),
     /// Export unexported transactions to per-account CSV files
     ///
     /// This command scans the ledger for transactions that have not yet been
     /// exported and writes one CSV file per account to the specified output
     /// directory (default: current working directory). Each exported record is
     /// marked with the export date so it will not appear in future exports.
+    /// The columns, date format, currency, files and marking can be changed
+    /// with the export profiles of the configuration.
     /// Transfer transactions are collapsed to a single entry on the source
     /// account side, with the destination account name used as the payee.
+    /// With the journal target, the whole ledger is written instead as a
+    /// journal of hledger/ledger-cli (and nothing is marked as exported).
     Export(cmd::export::Args),
+    /// Import the transactions of a bank statement
+    ///
+    /// This command will read a CSV statement, with the format described by
+    /// one of the import profiles of the configuration (columns, date
+    /// format, decimal separator and sign of the amounts), or an OFX/QFX
+    /// statement (with its accounts mapped in the configuration), and set the
+    /// category, venue, account or description of each transaction with the
+    /// first import rule whose pattern matches its description. Transactions
+    /// already in the ledger (same account, date and amount) or imported
+    /// before (same OFX identifier) are skipped.
+    Import(cmd::import::Args),
+    /// Assign an identifier to transfers booked without one
+    ///
+    /// Both legs of a transfer share an identifier (the Transfer column),
+    /// which is how reports and exports pair them. Transfers booked before
+    /// this column existed were paired by their position in the ledger, with
+    /// each leg followed by the other one. This command assigns an
+    /// identifier to those legs, pairing them in the same way, and warns
+    /// about legs that do not balance or have no counterpart.
+    Link(cmd::link::Args),
     /// Calculate current networth
     ///
     /// This command will print the list of the current networth, per asset.
     /// If the storage option is provided, then the total amount of the
     /// current networth is stored in the networth CSV as a new entry.
     Networth(cmd::networth::Args),
+    /// Book the recurring transactions that are due
+    ///
+    /// This command will book in the ledger every occurrence of the recurring
+    /// transactions (defined in the file `files.recurring` of the
+    /// configuration) since the last time it was run and until today. Lines
+    /// that are already in the ledger are not booked again, so it can be run
+    /// any number of times (e.g. from cron).
+    Recur(cmd::recur::Args),
+    /// Change the password of the encrypted files
+    ///
+    /// This command will decrypt the ledger and networth files with the
+    /// current password and encrypt them with a new one (asked for, or
+    /// generated). Each file is only replaced once both were encrypted and
+    /// decrypted back successfully, and the configuration is then updated
+    /// with the new password.
+    Rekey(cmd::rekey::Args),
     /// Create a report about the transactions on the ledger
     ///
     /// This command will generate a report, based on a defined time period,
@@ -111,6 +160,14 @@ pub enum Commands {
     /// no distinction made regarding different accounts - transactions are
     /// only aggregate per category.
     Report(cmd::report::Args),
+    /// List the backups of the ledger/networth file or restore one
+    ///
+    /// Every time the file is changed, a copy of the previous version is kept
+    /// (as it was stored, encrypted or not) in the directory of backups, up
+    /// to the number of copies set in the configuration. Without arguments,
+    /// this command lists them, from the most recent; given the number of
+    /// one of them, the file is replaced with it.
+    Restore(cmd::restore::Args),
     /// Display all transactions
     ///
     /// This command will generate a report, based on a defined time period,
@@ -126,12 +183,15 @@ pub enum Commands {
     /// the date of each entry. Unless the date is different, the entries
     /// should remain unchanged (date is the only attribute used for sorting).
     Sort(cmd::sort::Args),
-}
-
-#[derive(ValueEnum, Clone, Copy, Debug)]
-pub enum Mode {
-    Ledger,
-    Networth,
+    /// Book a transfer between two accounts
+    ///
+    /// This command will add both lines of a transfer to the ledger at once,
+    /// with the category defined for transfers and a shared identifier. The
+    /// amount leaves the source account and arrives to the destination one,
+    /// possibly in another currency: either the amount received is provided
+    /// (and the implied rate is displayed) or it is calculated with the rates
+    /// of the date of the transfer.
+    Transfer(cmd::transfer::Args),
 }
 
 fn main() {
@@ -141,18 +201,33 @@ fn main() {
 
     env_logger::init();
 
-    let result = match App::parse().command {
-        Commands::Balance(args) => cmd::balance::run(args),
+    let app = App::parse();
+
+    let format = app.format;
+
+    let result = match app.command {
+        Commands::Agent(args) => cmd::agent::run(args),
+        Commands::Balance(args) => cmd::balance::run(args, format),
         Commands::Book(args) => cmd::book::run(args),
+        Commands::Budget(args) => cmd::budget::run(args, format),
+        Commands::Check(args) => cmd::check::run(args, format),
+        Commands::Decrypt(args) => cmd::decrypt::run(args),
         Commands::Edit(args) => cmd::edit::run(args),
+        Commands::Encrypt(args) => cmd::encrypt::run(args),
         Commands::Export(args) => cmd::export::run(args),
+        Commands::Import(args) => cmd::import::run(args),
+        Commands::Link(args) => cmd::link::run(args),
         Commands::Configure(args) => cmd::configure::run(args),
         Commands::Convert(args) => cmd::convert::run(args),
         Commands::Create(args) => cmd::create::run(args),
-        Commands::Networth(args) => cmd::networth::run(args),
-        Commands::Report(args) => cmd::report::run(args),
+        Commands::Networth(args) => cmd::networth::run(args, format),
+        Commands::Recur(args) => cmd::recur::run(args),
+        Commands::Rekey(args) => cmd::rekey::run(args),
+        Commands::Report(args) => cmd::report::run(args, format),
+        Commands::Restore(args) => cmd::restore::run(args),
         Commands::Show(args) => cmd::show::run(args),
         Commands::Sort(args) => cmd::sort::run(args),
+        Commands::Transfer(args) => cmd::transfer::run(args),
     };
 
     match result {
@@ -0,0 +1,148 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use std::io::Write;
+
+/// How the reports are written to the standard output.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum Format {
+    /// Tables for the terminal (with colors).
+    #[default]
+    Table,
+    /// A single JSON document.
+    Json,
+    /// One CSV table per section, separated by an empty line.
+    Csv,
+    /// One Markdown table per section.
+    Markdown,
+}
+
+/// A table of a report, with every value as text.
+#[derive(Debug, Default)]
+pub struct Section {
+    pub title: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Section {
+    pub fn new(title: &str, headers: &[&str]) -> Self {
+        Self {
+            title: title.to_string(),
+            headers: headers.iter().map(|v| v.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    // Rows with less values than headers (e.g. totals) are completed with empty values.
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let mut values = row.clone();
+                values.resize(self.headers.len().max(row.len()), String::new());
+                values
+            })
+            .collect()
+    }
+}
+
+/// The report as JSON, from its `Serialize` implementation.
+pub trait Value {
+    fn value(&self) -> serde_json::Result<serde_json::Value>;
+}
+
+impl<T: Serialize> Value for T {
+    fn value(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+/// A report that can be written in any of the formats.
+pub trait Render: Value {
+    /// The tables of the report, for CSV and Markdown.
+    fn sections(&self) -> anyhow::Result<Vec<Section>>;
+
+    /// The report as tables for the terminal.
+    fn display(&self) -> anyhow::Result<()>;
+}
+
+/// Write the parts of the output of a command (e.g. a report and its summary). For JSON, a single
+/// part is written as is, while multiple parts are written as an object, by name.
+pub fn print(format: Format, parts: &[(&str, &dyn Render)]) -> anyhow::Result<()> {
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+
+    match format {
+        Format::Table => {
+            for (_, part) in parts {
+                part.display()?;
+            }
+        }
+        Format::Json => {
+            let value = match parts {
+                [(_, part)] => part.value()?,
+                _ => {
+                    let mut map = serde_json::Map::new();
+
+                    for (name, part) in parts {
+                        map.insert(name.to_string(), part.value()?);
+                    }
+
+                    serde_json::Value::Object(map)
+                }
+            };
+
+            writeln!(handle, "{}", serde_json::to_string_pretty(&value)?)?;
+        }
+        Format::Csv => {
+            for (index, section) in sections(parts)?.iter().enumerate() {
+                if index > 0 {
+                    writeln!(handle)?;
+                }
+
+                let mut wtr = csv::Writer::from_writer(&mut handle);
+
+                wtr.write_record(&section.headers)?;
+
+                for row in section.rows() {
+                    wtr.write_record(row)?;
+                }
+
+                wtr.flush()?;
+            }
+        }
+        Format::Markdown => {
+            for (index, section) in sections(parts)?.iter().enumerate() {
+                if index > 0 {
+                    writeln!(handle)?;
+                }
+
+                writeln!(handle, "### {}", section.title)?;
+                writeln!(handle)?;
+                writeln!(handle, "| {} |", escape(&section.headers).join(" | "))?;
+                writeln!(handle, "|{}", " --- |".repeat(section.headers.len()))?;
+
+                for row in section.rows() {
+                    writeln!(handle, "| {} |", escape(&row).join(" | "))?;
+                }
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn sections(parts: &[(&str, &dyn Render)]) -> anyhow::Result<Vec<Section>> {
+    let mut result = Vec::new();
+
+    for (_, part) in parts {
+        result.extend(part.sections()?);
+    }
+
+    Ok(result)
+}
+
+fn escape(values: &[String]) -> Vec<String> {
+    values.iter().map(|v| v.replace('|', "\\|")).collect()
+}
@@ -0,0 +1,249 @@
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::xdg::Xdg;
+
+const AGENT_FILENAME: &str = "agent.sock";
+
+/// Where the password of the encrypted files comes from (`password` in the configuration), other
+/// than `encryption`. The sources are tried in the order of the fields, and the first one that
+/// is set is used.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Settings {
+    /// Environment variable with the password.
+    #[serde(default = "Settings::default_env")]
+    pub env: String,
+    /// Command whose first line of output is the password (e.g. `pass show ledger`).
+    #[serde(default)]
+    pub command: Option<String>,
+    /// File with the password, only readable by its owner (mode 0600).
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Whether the password is asked for when no other source has it.
+    #[serde(default)]
+    pub prompt: bool,
+    /// Seconds during which the password asked for is kept by an agent (0 to always ask).
+    #[serde(default = "Settings::default_cache")]
+    pub cache: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            env: Self::default_env(),
+            command: None,
+            file: None,
+            prompt: false,
+            cache: Self::default_cache(),
+        }
+    }
+}
+
+impl Settings {
+    fn default_env() -> String {
+        "LEDGER_PASSWORD".to_string()
+    }
+
+    fn default_cache() -> u64 {
+        300
+    }
+
+    /// Whether the password is asked for (no other source is set).
+    pub fn prompted(&self) -> bool {
+        std::env::var_os(&self.env).is_none()
+            && self.command.is_none()
+            && self.file.is_none()
+            && self.prompt
+    }
+
+    pub fn resolve(&self) -> anyhow::Result<Option<String>> {
+        if let Some(value) = std::env::var_os(&self.env) {
+            return Ok(Some(value.to_string_lossy().into_owned()));
+        }
+
+        if let Some(command) = &self.command {
+            return command_pass(command).map(Some);
+        }
+
+        if let Some(file) = &self.file {
+            return file_pass(&shellexpand::tilde(file)).map(Some);
+        }
+
+        if self.prompt {
+            return self.prompt_pass().map(Some);
+        }
+
+        Ok(None)
+    }
+
+    // The agent is only available with a runtime directory ($XDG_RUNTIME_DIR), which is private
+    // to the user; without one, the password is asked every time.
+    fn prompt_pass(&self) -> anyhow::Result<String> {
+        let socket = match self.cache {
+            0 => None,
+            _ => Xdg::Runtime(AGENT_FILENAME.to_string()).filepath().ok(),
+        };
+
+        if let Some(pass) = socket.as_ref().and_then(|path| cached(path)) {
+            return Ok(pass);
+        }
+
+        let pass = rpassword::prompt_password("Password: ")?;
+
+        if socket.is_some() {
+            spawn(&pass, self.cache)?;
+        }
+
+        Ok(pass)
+    }
+}
+
+/// Ask for a password twice (e.g. `New password`), since a typo would leave the files encrypted
+/// with a password nobody knows.
+pub fn confirm(label: &str) -> anyhow::Result<String> {
+    let password = rpassword::prompt_password(format!("{}: ", label))?;
+
+    if password.is_empty() {
+        return Err(anyhow!("The password cannot be empty"));
+    }
+
+    let repeated = rpassword::prompt_password(format!("Repeat the {}: ", label.to_lowercase()))?;
+
+    if repeated != password {
+        return Err(anyhow!("The passwords do not match"));
+    }
+
+    Ok(password)
+}
+
+/// Keep the password in memory and hand it to whoever connects to the socket of the agent, until
+/// the time given has passed or the socket is removed (see `forget`). The password is read from
+/// the standard input.
+pub fn serve(seconds: u64) -> anyhow::Result<()> {
+    let mut pass = String::new();
+    std::io::stdin().read_to_string(&mut pass)?;
+
+    let path = Xdg::Runtime(AGENT_FILENAME.to_string()).filepath()?;
+
+    // A socket left by an agent that already stopped.
+    if fs::metadata(&path).is_ok() {
+        fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    // Without blocking, so that the deadline is checked between connections.
+    listener.set_nonblocking(true)?;
+
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+
+    // The socket might have been replaced by the one of another agent in the meantime.
+    let inode = fs::metadata(&path)?.ino();
+    let owned = || fs::metadata(&path).is_ok_and(|metadata| metadata.ino() == inode);
+
+    let mut result = Ok(());
+
+    while Instant::now() < deadline && owned() {
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                stream.set_nonblocking(false).ok();
+                stream.write_all(pass.as_bytes()).ok();
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => {
+                result = Err(e.into());
+                break;
+            }
+        }
+    }
+
+    if owned() {
+        fs::remove_file(&path)?;
+    }
+
+    result
+}
+
+/// Stop handing out the password kept by the agent (e.g. once it is changed, or when it turns out
+/// to be wrong), which then stops.
+pub fn forget() {
+    if let Ok(path) = Xdg::Runtime(AGENT_FILENAME.to_string()).filepath() {
+        fs::remove_file(path).ok();
+    }
+}
+
+fn cached(path: &str) -> Option<String> {
+    let mut stream = UnixStream::connect(path).ok()?;
+
+    let mut pass = String::new();
+    stream.read_to_string(&mut pass).ok()?;
+
+    Some(pass).filter(|value| !value.is_empty())
+}
+
+fn spawn(pass: &str, seconds: u64) -> anyhow::Result<()> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["agent", "--timeout", &seconds.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Away from the terminal, so that it is not stopped with the command that started it.
+        .process_group(0)
+        .spawn()
+        .context("Could not start the password agent")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(pass.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn command_pass(command: &str) -> anyhow::Result<String> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("Could not run the password command '{}'", command))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "The password command '{}' failed ({})",
+            command,
+            output.status
+        ));
+    }
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    match stdout.lines().next() {
+        Some(line) if !line.is_empty() => Ok(line.to_string()),
+        _ => Err(anyhow!("The password command '{}' had no output", command)),
+    }
+}
+
+fn file_pass(path: &str) -> anyhow::Result<String> {
+    let metadata = fs::metadata(path).with_context(|| format!("Could not read '{}'", path))?;
+
+    if metadata.permissions().mode() & 0o077 != 0 {
+        return Err(anyhow!(
+            "The key file '{}' must only be accessible by its owner (chmod 600)",
+            path
+        ));
+    }
+
+    let content = fs::read_to_string(path)?;
+
+    Ok(content.trim_end_matches(['\r', '\n']).to_string())
+}
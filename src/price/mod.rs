@@ -0,0 +1,267 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::entity::date::Date;
+use crate::entity::money::{Currency, Money};
+use crate::exchange::Exchange;
+use crate::xdg::Xdg;
+
+pub mod provider;
+
+use provider::Provider;
+
+const PRICE_CACHE_FILENAME: &str = "prices.yml";
+const PRICE_CACHE_TTL: i64 = 43200; // 12 hours
+
+/// A security (identified by its ISIN) and its current price.
+#[derive(Debug, Clone)]
+pub struct Asset {
+    pub isin: String,
+    pub name: String,
+    pub quote: Money,
+    /// When the price was obtained from the provider.
+    pub fetched: DateTime<Utc>,
+    /// Whether the provider failed and the price is the last one known.
+    pub stale: bool,
+}
+
+impl Asset {
+    pub fn new(isin: &str, name: &str, quote: Money) -> Self {
+        Self {
+            isin: isin.to_string(),
+            name: name.to_string(),
+            quote,
+            fetched: Utc::now(),
+            stale: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Quote {
+    name: String,
+    price: String,
+    currency: Currency,
+    fetched: DateTime<Utc>,
+}
+
+impl From<&Asset> for Quote {
+    fn from(asset: &Asset) -> Self {
+        Self {
+            name: asset.name.to_string(),
+            price: asset.quote.to_storage(),
+            currency: asset.quote.currency(),
+            fetched: asset.fetched,
+        }
+    }
+}
+
+impl Quote {
+    fn asset(&self, isin: &str) -> anyhow::Result<Asset> {
+        Ok(Asset {
+            isin: isin.to_string(),
+            name: self.name.to_string(),
+            quote: Money::parse(&self.price, self.currency)?,
+            fetched: self.fetched,
+            stale: false,
+        })
+    }
+
+    fn valid(&self) -> bool {
+        Utc::now().signed_duration_since(self.fetched).num_seconds() < PRICE_CACHE_TTL
+    }
+}
+
+/// The last known price of each asset, kept in the cache directory.
+#[derive(Debug)]
+pub struct Cache {
+    filepath: String,
+}
+
+impl Cache {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            filepath: Xdg::Cache(PRICE_CACHE_FILENAME.to_string()).filepath()?,
+        })
+    }
+
+    // A cache that cannot be read is only a cache: the prices are fetched again.
+    fn load(&self) -> anyhow::Result<BTreeMap<String, Quote>> {
+        if !Path::new(&self.filepath).exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        match serde_yaml::from_reader(File::open(&self.filepath)?) {
+            Ok(quotes) => Ok(quotes),
+            Err(e) => {
+                log::warn!("Ignoring the cache of prices '{}': {}", self.filepath, e);
+
+                Ok(BTreeMap::new())
+            }
+        }
+    }
+
+    // Written next to the cache and moved over it, so that an interrupted write does not lose
+    // the last prices known (used when the providers fail).
+    fn store(&self, quotes: &BTreeMap<String, Quote>) -> anyhow::Result<()> {
+        let directory = Path::new(&self.filepath)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        let yaml = serde_yaml::to_string(quotes)?;
+        file.write_all(yaml.as_bytes())?;
+        file.persist(&self.filepath).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
+
+/// The price providers configured, by ISIN.
+///
+/// Prices are cached for 12 hours (unless `refresh` is set) and, when a provider fails, the last
+/// price known for the asset is used instead (marked as stale). Prices missing from the cache are
+/// downloaded concurrently, by a limited number of workers.
+#[derive(Debug)]
+pub struct Prices {
+    default: Box<dyn Provider>,
+    assets: HashMap<String, Box<dyn Provider>>,
+    cache: Cache,
+    quotes: BTreeMap<String, Quote>,
+    refresh: bool,
+    workers: usize,
+}
+
+impl Prices {
+    pub fn new(config: &Config, refresh: bool) -> anyhow::Result<Prices> {
+        let timeout = Duration::from_secs(config.prices.timeout);
+
+        let mut assets = HashMap::new();
+
+        for (isin, source) in config.prices.assets.iter() {
+            assets.insert(isin.to_string(), provider::build(source, timeout)?);
+        }
+
+        let cache = Cache::new()?;
+
+        Ok(Prices {
+            default: provider::build(&config.prices.default, timeout)?,
+            assets,
+            quotes: cache.load()?,
+            cache,
+            refresh,
+            workers: config.prices.workers.max(1),
+        })
+    }
+
+    /// Current price of each asset, in the currency provided. Each asset has its own result, so
+    /// that failing to obtain one of them does not prevent the others from being valued.
+    pub fn assets(
+        &mut self,
+        isins: &[String],
+        currency: Currency,
+        exchange: &Exchange,
+    ) -> anyhow::Result<HashMap<String, anyhow::Result<Asset>>> {
+        let (cached, missing): (Vec<&String>, Vec<&String>) = isins.iter().partition(|isin| {
+            !self.refresh && self.quotes.get(*isin).is_some_and(|quote| quote.valid())
+        });
+
+        let mut result = HashMap::new();
+
+        for isin in cached {
+            result.insert(isin.to_string(), self.quotes[isin].asset(isin));
+        }
+
+        let downloaded = self.download(&missing, currency);
+
+        for (isin, download) in downloaded {
+            let asset = match download {
+                Ok(asset) => {
+                    self.quotes.insert(isin.to_string(), (&asset).into());
+                    Ok(asset)
+                }
+                Err(e) => match self.quotes.get(&isin) {
+                    Some(quote) => {
+                        log::warn!("Using last known price of '{}': {}", isin, e);
+
+                        quote.asset(&isin).map(|asset| Asset {
+                            stale: true,
+                            ..asset
+                        })
+                    }
+                    None => Err(e),
+                },
+            };
+
+            result.insert(isin, asset);
+        }
+
+        if !missing.is_empty() {
+            self.cache.store(&self.quotes)?;
+        }
+
+        Ok(result
+            .into_iter()
+            .map(|(isin, asset)| {
+                let exchanged = asset.and_then(|value| {
+                    Ok(Asset {
+                        quote: value.quote.exchange(currency, exchange, Date::today())?,
+                        ..value
+                    })
+                });
+
+                (isin, exchanged)
+            })
+            .collect())
+    }
+
+    fn download(
+        &self,
+        isins: &[&String],
+        currency: Currency,
+    ) -> Vec<(String, anyhow::Result<Asset>)> {
+        let queue = Mutex::new(isins.iter());
+        let default = &self.default;
+        let assets = &self.assets;
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.workers.min(isins.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut result = Vec::new();
+
+                        loop {
+                            // Release the lock before downloading, so workers run concurrently.
+                            let next = queue.lock().ok().and_then(|mut v| v.next());
+
+                            match next {
+                                Some(isin) => {
+                                    let provider = assets.get(*isin).unwrap_or(default);
+
+                                    result.push((isin.to_string(), provider.quote(isin, currency)));
+                                }
+                                None => break,
+                            }
+                        }
+
+                        result
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|_| vec![]))
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::time::Duration;
+
+use crate::entity::money::{Currency, Money};
+use crate::price::Asset;
+use crate::service::justetf;
+
+/// Where the price of an asset comes from (`prices.default` and `prices.assets` in the
+/// configuration).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Source {
+    /// Download the price from justETF (only ETFs are listed there).
+    #[default]
+    JustEtf,
+    /// Read the price from a local YAML file, maintained by hand.
+    Manual { path: String },
+    /// Download a JSON document and take the price (and name) from it.
+    Json {
+        /// Address of the document, where {ISIN} and {CURRENCY} are replaced accordingly.
+        url: String,
+        /// JSON pointer (RFC 6901) to the price, e.g. "/data/0/price".
+        price: String,
+        /// JSON pointer to the name of the asset (the ISIN is used when not provided).
+        #[serde(default)]
+        name: Option<String>,
+        /// Currency of the price (the one requested is assumed when not provided).
+        #[serde(default)]
+        currency: Option<String>,
+    },
+}
+
+/// A source of prices for assets. Prices of different assets are requested concurrently.
+pub trait Provider: std::fmt::Debug + Send + Sync {
+    /// Current price of the asset. The price is returned in the currency provided if the source
+    /// supports it, otherwise in the currency used by the source.
+    fn quote(&self, isin: &str, currency: Currency) -> anyhow::Result<Asset>;
+}
+
+pub fn build(source: &Source, timeout: Duration) -> anyhow::Result<Box<dyn Provider>> {
+    let http = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()?;
+
+    match source {
+        Source::JustEtf => Ok(Box::new(JustEtf { http })),
+        Source::Manual { path } => Ok(Box::new(Manual::load(&shellexpand::tilde(path))?)),
+        Source::Json {
+            url,
+            price,
+            name,
+            currency,
+        } => Ok(Box::new(Json {
+            http,
+            url: url.to_string(),
+            price: price.to_string(),
+            name: name.clone(),
+            currency: currency.as_deref().map(Currency::parse).transpose()?,
+        })),
+    }
+}
+
+#[derive(Debug)]
+pub struct JustEtf {
+    http: reqwest::blocking::Client,
+}
+
+impl Provider for JustEtf {
+    fn quote(&self, isin: &str, currency: Currency) -> anyhow::Result<Asset> {
+        justetf::download(&self.http, isin, &currency)
+    }
+}
+
+/// Prices kept in a YAML file, as a map of ISIN to name, price and currency:
+///
+/// ```yaml
+/// US0378331005:
+///   name: Apple Inc.
+///   price: 189.25
+///   currency: USD
+/// ```
+#[derive(Debug)]
+pub struct Manual {
+    prices: BTreeMap<String, ManualPrice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManualPrice {
+    #[serde(default)]
+    name: Option<String>,
+    price: f64,
+    currency: Currency,
+}
+
+impl Manual {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let file = File::open(path).with_context(|| format!("Could not open '{}'", path))?;
+
+        Ok(Self {
+            prices: serde_yaml::from_reader(file)?,
+        })
+    }
+}
+
+impl Provider for Manual {
+    fn quote(&self, isin: &str, _currency: Currency) -> anyhow::Result<Asset> {
+        let value = self
+            .prices
+            .get(isin)
+            .ok_or_else(|| anyhow!("There is no price defined for '{}'", isin))?;
+
+        Ok(Asset::new(
+            isin,
+            value.name.as_deref().unwrap_or(isin),
+            Money::parse(&value.price.to_string(), value.currency)?,
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct Json {
+    http: reqwest::blocking::Client,
+    url: String,
+    price: String,
+    name: Option<String>,
+    currency: Option<Currency>,
+}
+
+impl Provider for Json {
+    fn quote(&self, isin: &str, currency: Currency) -> anyhow::Result<Asset> {
+        let url = self
+            .url
+            .replace("{ISIN}", isin)
+            .replace("{CURRENCY}", &currency.code());
+
+        let body = self.http.get(url).send()?.text()?;
+
+        let data: Value = serde_json::from_str(&body)?;
+
+        let price = match data.pointer(&self.price) {
+            Some(Value::String(value)) => value.to_string(),
+            Some(Value::Number(value)) => value.to_string(),
+            _ => return Err(anyhow!("Asset value could not be found for '{}'", isin)),
+        };
+
+        let name = match &self.name {
+            Some(pointer) => data
+                .pointer(pointer)
+                .and_then(|val| val.as_str())
+                .ok_or_else(|| anyhow!("Asset name could not be found for '{}'", isin))?
+                .to_string(),
+            None => isin.to_string(),
+        };
+
+        Ok(Asset::new(
+            isin,
+            &name,
+            Money::parse(&price, self.currency.unwrap_or(currency))?,
+        ))
+    }
+}
@@ -1,19 +1,25 @@
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use lockfile::Lockfile;
 use tempfile::NamedTempFile;
 
+use std::cell::Cell;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::Seek;
+use std::path::{Path, PathBuf};
 
 use crate::entity::line::{Line, Liner};
 use crate::entity::{entry, transaction};
-use crate::{config, crypto, Mode};
+use crate::{backup, config, crypto, password, Error, Mode};
 
 pub struct Resource {
     pub filepath: String,
     pub tempfile: NamedTempFile,
     pass: Option<String>,
+    kdf: crypto::Strength,
+    backups: (String, usize),
+    // Only the file before the first change is backed up, however many times it is replaced.
+    backed_up: Cell<bool>,
     mode: Mode,
     _lock: Lockfile,
 }
@@ -23,7 +29,10 @@ impl Resource {
         let filepath = config.filepath(mode);
 
         Ok(Resource {
-            pass: config.pass(),
+            pass: config.pass()?,
+            kdf: config.kdf(),
+            backups: (config.backups_path()?, config.backups.keep),
+            backed_up: Cell::new(false),
             filepath: filepath.to_string(),
             tempfile: tempfile::Builder::new().suffix(".csv").tempfile()?,
             mode,
@@ -68,6 +77,8 @@ impl Resource {
     }
 
     pub fn book(&mut self, lines: &[Line]) -> anyhow::Result<()> {
+        self.upgrade()?;
+
         self.apply(|file| {
             let afile = OpenOptions::new().append(true).open(file.path())?;
             let mut wtr = csv::WriterBuilder::new()
@@ -97,6 +108,16 @@ impl Resource {
         Ok(())
     }
 
+    // Reading the file leaves it as it is, without writing it back (or backing it up).
+    fn view<F>(&mut self, action: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(&NamedTempFile) -> anyhow::Result<()>,
+    {
+        self.open()?;
+
+        action(&self.tempfile)
+    }
+
     pub fn rewrite<F>(&mut self, action: &mut F) -> anyhow::Result<()>
     where
         F: FnMut(&mut Line) -> anyhow::Result<Vec<Line>>,
@@ -120,13 +141,67 @@ impl Resource {
         Ok(())
     }
 
+    /// Load every line of the file, in the order they are stored.
+    pub fn lines(&mut self) -> anyhow::Result<Vec<Line>> {
+        let mut lines = Vec::new();
+
+        self.line(&mut |record| {
+            lines.push(record.clone());
+            Ok(())
+        })?;
+
+        Ok(lines)
+    }
+
+    /// Load every line of the file with its line number, keeping the ones that could not be read
+    /// as errors instead of stopping at the first one.
+    pub fn records(&mut self) -> anyhow::Result<Vec<(u64, anyhow::Result<Line>)>> {
+        let mode = self.mode;
+
+        let mut records = Vec::new();
+
+        self.view(|file| {
+            let mut rdr = csv::Reader::from_reader(file);
+
+            let headers = rdr.headers()?.clone();
+
+            for result in rdr.records() {
+                let record = match result {
+                    Ok(value) => value,
+                    Err(e) => {
+                        let line = e.position().map(|v| v.line()).unwrap_or_default();
+                        records.push((line, Err(e.into())));
+                        continue;
+                    }
+                };
+
+                let line = record.position().map(|v| v.line()).unwrap_or_default();
+
+                let value = match mode {
+                    Mode::Ledger => record
+                        .deserialize::<transaction::Transaction>(Some(&headers))
+                        .map(Line::from),
+                    Mode::Networth => record
+                        .deserialize::<entry::Entry>(Some(&headers))
+                        .map(Line::from),
+                };
+
+                records.push((line, value.map_err(|e| e.into())));
+            }
+
+            Ok(())
+        })?;
+
+        Ok(records)
+    }
+
     pub fn line<F>(&mut self, action: &mut F) -> anyhow::Result<()>
     where
         F: FnMut(&mut Line) -> anyhow::Result<()>,
     {
         let mode = self.mode;
 
-        self.apply(|file| {
+        self.view(|file| {
             let mut rdr = csv::Reader::from_reader(file);
 
             match mode {
@@ -148,23 +223,289 @@ impl Resource {
         Ok(())
     }
 
+    /// Encrypt the content of the file with another password, into a new file next to it that
+    /// only replaces the original once it is persisted. The new file is decrypted back to check
+    /// that it holds exactly the same content.
+    pub fn reencrypt(&mut self, password: &str) -> anyhow::Result<NamedTempFile> {
+        self.open()?;
+
+        self.seal(password)
+    }
+
+    /// Encrypt the file, stored in plain text, with the password of the configuration.
+    pub fn encrypt(&mut self) -> anyhow::Result<()> {
+        let pass = self.pass.clone().ok_or_else(|| {
+            anyhow!(
+                "There is no password in the configuration to encrypt '{}' with",
+                self.filepath
+            )
+        })?;
+
+        if crypto::kind(&self.filepath)? != crypto::Kind::Plaintext {
+            return Err(anyhow!("'{}' is already encrypted", self.filepath));
+        }
+
+        std::fs::copy(&self.filepath, self.tempfile.path())?;
+
+        let nfile = self.seal(&pass)?;
+
+        self.replace(nfile)
+    }
+
+    /// Store the file in plain text, decrypted with the password of the configuration.
+    pub fn decrypt(&mut self) -> anyhow::Result<()> {
+        if crypto::kind(&self.filepath)? == crypto::Kind::Plaintext {
+            return Err(anyhow!("'{}' is not encrypted", self.filepath));
+        }
+
+        self.open()?;
+
+        let nfile = tempfile::Builder::new()
+            .suffix(".csv")
+            .tempfile_in(self.directory())?;
+
+        std::fs::copy(self.tempfile.path(), nfile.path())?;
+
+        self.replace(nfile)
+    }
+
+    /// Replace the file with the one of the backup given (the file replaced is backed up too).
+    /// A backup from before the file was encrypted or decrypted is stored as the file is now, and
+    /// the password of an encrypted backup is asked for when the current one does not open it.
+    pub fn restore(&mut self, backup: &Path) -> anyhow::Result<()> {
+        let path = backup.to_string_lossy().into_owned();
+
+        match (crypto::kind(&path)?, self.pass.clone()) {
+            (crypto::Kind::Plaintext, _) => self.load(&path, None)?,
+            (_, Some(pass)) if self.load(&path, Some(&pass)).is_ok() => {}
+            (_, _) => {
+                let pass = rpassword::prompt_password("Password of the backup: ")?;
+                self.load(&path, Some(&pass))?;
+            }
+        }
+
+        self.close(&self.tempfile)
+    }
+
+    /// A copy of the file as it is, next to it, to put it back with `replace` if needed.
+    pub fn snapshot(&self) -> anyhow::Result<NamedTempFile> {
+        let nfile = tempfile::Builder::new()
+            .suffix(".orig")
+            .tempfile_in(self.directory())?;
+
+        std::fs::copy(&self.filepath, nfile.path())?;
+
+        Ok(nfile)
+    }
+
+    /// Encrypt the backups of the file with the password given (e.g. once the file is encrypted,
+    /// or encrypted with a new password), so that none of them can still be read without it.
+    /// Returns the number of backups encrypted and the ones left as they were, since they could
+    /// not be opened with the current password.
+    pub fn reseal_backups(&self, password: &str) -> anyhow::Result<(usize, Vec<PathBuf>)> {
+        let (directory, _) = &self.backups;
+
+        let mut count = 0;
+        let mut skipped = Vec::new();
+
+        for backup in backup::list(directory, &self.filepath)? {
+            let content = tempfile::Builder::new().suffix(".csv").tempfile()?;
+
+            match (crypto::kind(&backup.path.to_string_lossy())?, &self.pass) {
+                (crypto::Kind::Plaintext, _) => {
+                    std::fs::copy(&backup.path, content.path())?;
+                }
+                // Already encrypted with it.
+                (_, Some(pass)) if pass == password => continue,
+                (_, Some(pass)) => {
+                    let mut in_file = File::open(&backup.path)?;
+
+                    if crypto::decrypt(&mut in_file, &mut content.reopen()?, pass).is_err() {
+                        skipped.push(backup.path);
+                        continue;
+                    }
+                }
+                (_, None) => {
+                    skipped.push(backup.path);
+                    continue;
+                }
+            }
+
+            let nfile = tempfile::Builder::new()
+                .suffix(".enc")
+                .tempfile_in(directory)?;
+
+            crypto::encrypt(
+                &mut content.reopen()?,
+                &mut nfile.reopen()?,
+                password,
+                self.kdf,
+            )?;
+
+            nfile.as_file().sync_all()?;
+            nfile.persist(&backup.path).map_err(|e| e.error)?;
+
+            count += 1;
+        }
+
+        Ok((count, skipped))
+    }
+
+    /// Replace the file with the new one given, keeping a backup of the current one. The new
+    /// file must be in the same directory, so that it can be moved over it at once.
+    pub fn replace(&self, nfile: NamedTempFile) -> anyhow::Result<()> {
+        nfile.as_file().sync_all()?;
+
+        if let Ok(metadata) = std::fs::metadata(&self.filepath) {
+            std::fs::set_permissions(nfile.path(), metadata.permissions())?;
+        }
+
+        if !self.backed_up.replace(true) {
+            let (directory, keep) = &self.backups;
+            backup::store(directory, &self.filepath, *keep)?;
+        }
+
+        nfile.persist(&self.filepath).map_err(|e| e.error)?;
+
+        // The rename is only durable once the directory is written too.
+        if let Ok(directory) = File::open(self.directory()) {
+            directory.sync_all().ok();
+        }
+
+        Ok(())
+    }
+
+    fn directory(&self) -> &Path {
+        match Path::new(&self.filepath).parent() {
+            Some(path) if !path.as_os_str().is_empty() => path,
+            _ => Path::new("."),
+        }
+    }
+
+    // Encrypt the content loaded into a new file next to the original one, checking that it can
+    // be decrypted back to the same content.
+    fn seal(&self, password: &str) -> anyhow::Result<NamedTempFile> {
+        let nfile = tempfile::Builder::new()
+            .suffix(".enc")
+            .tempfile_in(self.directory())?;
+
+        crypto::encrypt(
+            &mut self.tempfile.reopen()?,
+            &mut nfile.reopen()?,
+            password,
+            self.kdf,
+        )?;
+
+        let check = tempfile::Builder::new().suffix(".csv").tempfile()?;
+
+        crypto::decrypt(&mut nfile.reopen()?, &mut check.reopen()?, password)?;
+
+        if std::fs::read(check.path())? != std::fs::read(self.tempfile.path())? {
+            return Err(anyhow!(
+                "The content of '{}' changed when encrypted",
+                self.filepath
+            ));
+        }
+
+        Ok(nfile)
+    }
+
+    /// Rewrite the file with the current headers if it was created with older ones (e.g. before
+    /// the `Transfer` column existed), so that new lines can be appended to it.
+    fn upgrade(&mut self) -> anyhow::Result<()> {
+        let expected: Vec<String> = self.headers().iter().map(|v| v.to_string()).collect();
+
+        let mut outdated = false;
+
+        self.view(|file| {
+            let mut rdr = csv::Reader::from_reader(file);
+
+            outdated = !rdr.headers()?.iter().eq(expected.iter().map(|v| v.as_str()));
+
+            Ok(())
+        })?;
+
+        if !outdated {
+            return Ok(());
+        }
+
+        let lines = self.lines()?;
+
+        let nfile = tempfile::Builder::new().suffix(".csv").tempfile()?;
+
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_path(nfile.path())?;
+
+        wtr.write_record(&expected)?;
+
+        for line in lines {
+            line.write(&mut wtr)?;
+        }
+
+        wtr.flush()?;
+
+        self.close(&nfile)
+    }
+
     fn open(&mut self) -> anyhow::Result<()> {
-        match self.pass.take() {
+        let filepath = self.filepath.to_string();
+
+        self.read(&filepath)
+    }
+
+    // Files are decrypted based on how they are stored, and never read as plain text when they
+    // are encrypted (or written encrypted when they are not), which would lose their content.
+    fn read(&mut self, filepath: &str) -> anyhow::Result<()> {
+        let pass = match (crypto::kind(filepath)?, &self.pass) {
+            (crypto::Kind::Plaintext, None) => None,
+            (crypto::Kind::Plaintext, Some(_)) => {
+                return Err(anyhow!(
+                    "'{}' is not encrypted, use `ledger encrypt` to encrypt it with the password \
+                     of the configuration (or remove the password)",
+                    filepath
+                ));
+            }
+            (_, None) => {
+                return Err(anyhow!(
+                    "'{}' is encrypted, but there is no password in the configuration",
+                    filepath
+                ));
+            }
+            (_, Some(pass)) => Some(pass.to_string()),
+        };
+
+        let result = self.load(filepath, pass.as_deref());
+
+        // A password typed wrong is not kept by the agent, so that it is asked again.
+        if result.is_err() && pass.is_some() {
+            password::forget();
+        }
+
+        result
+    }
+
+    // Load the content of the file into the temporary one, decrypted with the password given.
+    fn load(&mut self, filepath: &str, pass: Option<&str>) -> anyhow::Result<()> {
+        match pass {
+            None => {
+                std::fs::copy(filepath, self.tempfile.path())?;
+            }
             Some(pass) => {
-                let mut in_file = File::open(&self.filepath)?;
+                let mut in_file = File::open(filepath)?;
                 let mut out_file = self.tempfile.reopen()?;
 
-                match crypto::decrypt(&mut in_file, &mut out_file, &pass) {
-                    Ok(_) => {
-                        self.pass = Some(pass);
-                    }
-                    Err(_) => {
-                        std::fs::copy(&self.filepath, self.tempfile.path())?;
+                out_file.set_len(0)?;
+
+                crypto::decrypt(&mut in_file, &mut out_file, pass).map_err(|e| {
+                    match e.downcast_ref::<Error>() {
+                        Some(error) => anyhow!("Could not decrypt '{}': {}", filepath, error),
+                        None => e.context(format!(
+                            "Could not decrypt '{}' (incorrect password or damaged file)",
+                            filepath
+                        )),
                     }
-                };
-            }
-            None => {
-                std::fs::copy(&self.filepath, self.tempfile.path())?;
+                })?;
             }
         };
 
@@ -173,18 +514,24 @@ impl Resource {
         Ok(())
     }
 
+    // The new content is written next to the file and only then moved over it, so that the file
+    // is never left half written.
     fn close(&self, tempfile: &NamedTempFile) -> anyhow::Result<()> {
+        let nfile = tempfile::Builder::new()
+            .suffix(".csv")
+            .tempfile_in(self.directory())?;
+
         match &self.pass {
             Some(pass) => {
                 let mut in_file = tempfile.reopen()?;
-                let mut out_file = File::create(&self.filepath)?;
-                crypto::encrypt(&mut in_file, &mut out_file, pass)?;
+                let mut out_file = nfile.reopen()?;
+                crypto::encrypt(&mut in_file, &mut out_file, pass, self.kdf)?;
             }
             None => {
-                std::fs::copy(tempfile.path(), &self.filepath)?;
+                std::fs::copy(tempfile.path(), nfile.path())?;
             }
         };
 
-        Ok(())
+        self.replace(nfile)
     }
 }
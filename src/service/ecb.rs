@@ -0,0 +1,56 @@
+//! A parser for the euro foreign exchange reference rates published by the ECB.
+//!
+//! Both the daily (`eurofxref-daily.xml`) and the historical (`eurofxref-hist.xml`) feeds share
+//! the same structure: each day is a `Cube` with a `time` attribute, containing one `Cube` per
+//! currency (with the `currency` and `rate` attributes). All rates are relative to EUR.
+
+use anyhow::anyhow;
+
+use std::collections::BTreeMap;
+
+use crate::entity::date::Date;
+
+pub const BASE: &str = "EUR";
+
+/// Rates per day, as found in the feed.
+pub fn parse(content: &str) -> anyhow::Result<BTreeMap<Date, BTreeMap<String, f32>>> {
+    let mut result: BTreeMap<Date, BTreeMap<String, f32>> = BTreeMap::new();
+    let mut current: Option<Date> = None;
+
+    for element in content.split('<').skip(1) {
+        let tag = element.split('>').next().unwrap_or_default();
+
+        if !tag.starts_with("Cube") {
+            continue;
+        }
+
+        if let Some(time) = attribute(tag, "time") {
+            let date = Date::parse(time)?;
+
+            result
+                .entry(date)
+                .or_default()
+                .insert(BASE.to_string(), 1.0);
+
+            current = Some(date);
+        } else if let (Some(currency), Some(rate)) =
+            (attribute(tag, "currency"), attribute(tag, "rate"))
+        {
+            let date = current.ok_or_else(|| anyhow!("Rate for '{}' outside of a day", currency))?;
+
+            result
+                .entry(date)
+                .or_default()
+                .insert(currency.to_string(), rate.parse::<f32>()?);
+        }
+    }
+
+    Ok(result)
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=", name))? + name.len() + 2;
+    let quote = tag[start..].chars().next()?;
+
+    tag[start + 1..].split(quote).next()
+}
@@ -1,2 +1,3 @@
+pub mod ecb;
 pub mod justetf;
 pub mod openexchangerates;
@@ -4,6 +4,8 @@ use serde::Deserialize;
 
 use std::collections::BTreeMap;
 
+use crate::entity::date::Date;
+
 #[derive(Deserialize, Debug)]
 pub struct ExchangeRate {
     pub disclaimer: String,
@@ -42,4 +44,20 @@ impl Client {
 
         Ok(deserialized)
     }
+
+    /// Get the exchange rates at the end of a given day.
+    ///
+    /// The corresponding endpoint in OpenExchangeRates is documented in [here](https://docs.openexchangerates.org/docs/historical-json).
+    pub fn historical(self, date: Date) -> anyhow::Result<ExchangeRate> {
+        let url = &format!(
+            "https://openexchangerates.org/api/historical/{}.json?app_id={}",
+            date, self.app_id
+        );
+
+        let body = self.http.get(url).send()?.text()?;
+
+        let deserialized: ExchangeRate = serde_json::from_str(&body)?;
+
+        Ok(deserialized)
+    }
 }
@@ -12,16 +12,14 @@ pub fn editor() -> anyhow::Result<String> {
     std::env::var("EDITOR").context("EDITOR variable is not set")
 }
 
-pub fn random_pass() -> Option<String> {
+pub fn random_pass() -> String {
     let mut rng = rand::thread_rng();
     let bytes = iter::repeat(())
         .map(|()| rng.sample(rand::distributions::Alphanumeric))
         .take(32)
         .collect::<Vec<_>>();
 
-    let value = String::from_utf8_lossy(&bytes).into_owned();
-
-    Some(value)
+    String::from_utf8_lossy(&bytes).into_owned()
 }
 
 pub fn currency(value: Option<&String>, config: &Config) -> anyhow::Result<Currency> {
@@ -70,6 +68,19 @@ pub fn percentage_cell(dividend: &Money, divisor: &Money, alignment: Alignment)
         .with_style(color(value))
 }
 
+/// Share of the budget already spent, in red once it is exceeded.
+pub fn consumption_cell(actual: &Money, budget: &Money, alignment: Alignment) -> Cell {
+    let value = if budget.zero() {
+        100.0
+    } else {
+        (actual.cents().abs() as f64 / budget.cents() as f64) * 100.0
+    };
+
+    Cell::new_align(&format!("{:.2}%", value), alignment)
+        .with_style(Attr::Bold)
+        .with_style(color(100.0 - value))
+}
+
 fn color(value: f64) -> Attr {
     match value {
         v if v > 0.0 => Attr::ForegroundColor(color::BRIGHT_GREEN),
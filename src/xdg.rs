@@ -7,6 +7,7 @@ use std::fmt;
 pub enum Xdg {
     Config(String),
     Cache(String),
+    Runtime(String),
 }
 
 impl fmt::Display for Xdg {
@@ -14,6 +15,7 @@ impl fmt::Display for Xdg {
         match self {
             Xdg::Config(filename) => write!(f, "config({:?})", filename),
             Xdg::Cache(filename) => write!(f, "cache({:?})", filename),
+            Xdg::Runtime(filename) => write!(f, "runtime({:?})", filename),
         }
     }
 }
@@ -25,11 +27,12 @@ impl Xdg {
         let filepath = match self {
             Xdg::Config(filename) => directory.place_config_file(filename)?,
             Xdg::Cache(filename) => directory.place_cache_file(filename)?,
+            Xdg::Runtime(filename) => directory.place_runtime_file(filename)?,
         };
 
         filepath.to_str().map(|v| v.to_string()).ok_or(anyhow!(
             "An error occurred while determining the path for: {}",
-            self.to_string()
+            self
         ))
     }
 